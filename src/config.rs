@@ -0,0 +1,242 @@
+use std::io;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::de::Deserializer;
+use crate::error::Result;
+use crate::read::{IoRead, SliceRead};
+use crate::ser::{Serializer, SizeCounter};
+
+/// Nesting depth allowed by default, so that deeply nested input fails with
+/// an error instead of overflowing the stack.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Upper bounds enforced while decoding untrusted input. Every limit is
+/// checked before the bytes it guards are read or allocated.
+///
+/// The default only limits the nesting depth, to `DEFAULT_MAX_DEPTH`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limits {
+    /// Maximum encoded length of a string, in bytes.
+    pub max_string_length: Option<usize>,
+    /// Maximum length of a string in UTF-16 code units, the way the vanilla
+    /// server counts it.
+    pub max_string_utf16_length: Option<usize>,
+    /// Maximum number of elements in a sequence, map or byte array.
+    pub max_elements: Option<usize>,
+    /// Maximum number of bytes read from the input in total.
+    pub max_bytes: Option<usize>,
+    /// Maximum nesting depth of structs, newtype structs, options, sequences,
+    /// maps and enums.
+    pub max_depth: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_string_length: None,
+            max_string_utf16_length: None,
+            max_elements: None,
+            max_bytes: None,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+        }
+    }
+}
+
+/// Byte order of fixed-width integers and floats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+/// How `i16`/`u16` up to `i64`/`u64` are written. Single bytes, 128-bit
+/// integers, lengths and enum tags are not affected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IntEncoding {
+    Fixed,
+    VarInt,
+    /// Like `VarInt`, but signed integers are ZigZag-encoded first, as in
+    /// Bedrock Edition.
+    ZigZag,
+}
+
+/// How a `char` is written: a length-prefixed UTF-8 string, or a single
+/// UTF-16 code unit like Java's `char`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CharEncoding {
+    Utf8,
+    Utf16,
+}
+
+/// What to do with input left over once a value has been decoded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrailingBytes {
+    Allow,
+    Reject,
+}
+
+/// Encoding configuration shared by `Serializer` and `Deserializer`.
+///
+/// The default matches the Java Edition protocol: big-endian fixed-width
+/// integers, the default `Limits`, trailing bytes allowed, strict UTF-8
+/// decoding and chars written as strings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Options {
+    pub(crate) endian: Endian,
+    pub(crate) int_encoding: IntEncoding,
+    pub(crate) limits: Limits,
+    pub(crate) trailing_bytes: TrailingBytes,
+    pub(crate) strict_utf8: bool,
+    pub(crate) char_encoding: CharEncoding,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            endian: Endian::Big,
+            int_encoding: IntEncoding::Fixed,
+            limits: Limits::default(),
+            trailing_bytes: TrailingBytes::Allow,
+            strict_utf8: true,
+            char_encoding: CharEncoding::Utf8,
+        }
+    }
+}
+
+impl Options {
+    pub fn new() -> Self {
+        Options::default()
+    }
+
+    pub fn with_big_endian(mut self) -> Self {
+        self.endian = Endian::Big;
+        self
+    }
+
+    pub fn with_little_endian(mut self) -> Self {
+        self.endian = Endian::Little;
+        self
+    }
+
+    pub fn with_fixint_encoding(mut self) -> Self {
+        self.int_encoding = IntEncoding::Fixed;
+        self
+    }
+
+    pub fn with_varint_encoding(mut self) -> Self {
+        self.int_encoding = IntEncoding::VarInt;
+        self
+    }
+
+    pub fn with_zigzag_encoding(mut self) -> Self {
+        self.int_encoding = IntEncoding::ZigZag;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn allow_trailing_bytes(mut self) -> Self {
+        self.trailing_bytes = TrailingBytes::Allow;
+        self
+    }
+
+    pub fn reject_trailing_bytes(mut self) -> Self {
+        self.trailing_bytes = TrailingBytes::Reject;
+        self
+    }
+
+    pub fn with_strict_utf8(mut self) -> Self {
+        self.strict_utf8 = true;
+        self
+    }
+
+    /// Replace invalid UTF-8 sequences in strings instead of rejecting them.
+    pub fn with_lossy_utf8(mut self) -> Self {
+        self.strict_utf8 = false;
+        self
+    }
+
+    pub fn with_utf8_chars(mut self) -> Self {
+        self.char_encoding = CharEncoding::Utf8;
+        self
+    }
+
+    pub fn with_utf16_chars(mut self) -> Self {
+        self.char_encoding = CharEncoding::Utf16;
+        self
+    }
+
+    pub fn serializer<W: io::Write>(self, writer: W) -> Serializer<W> {
+        Serializer::new(writer).with_options(self)
+    }
+
+    pub fn deserializer_from_slice(self, slice: &[u8]) -> Deserializer<SliceRead<'_>> {
+        Deserializer::from_slice(slice).with_options(self)
+    }
+
+    pub fn deserializer_from_reader<R: io::Read>(self, reader: R) -> Deserializer<IoRead<R>> {
+        Deserializer::from_reader(reader).with_options(self)
+    }
+
+    pub fn serialize<T>(self, value: &T) -> Result<Vec<u8>>
+    where
+        T: ?Sized + Serialize,
+    {
+        let mut serializer = self.serializer(vec![]);
+        value.serialize(&mut serializer)?;
+        Ok(serializer.into_inner())
+    }
+
+    pub fn serialize_into<W, T>(self, writer: &mut W, value: &T) -> Result<()>
+    where
+        W: io::Write,
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut self.serializer(writer))
+    }
+
+    pub fn serialized_size<T>(self, value: &T) -> Result<usize>
+    where
+        T: ?Sized + Serialize,
+    {
+        let mut serializer = self.serializer(SizeCounter(0));
+        value.serialize(&mut serializer)?;
+        Ok(serializer.into_inner().0)
+    }
+
+    pub fn deserialize<'a, T>(self, bytes: &'a [u8]) -> Result<T>
+    where
+        T: Deserialize<'a>,
+    {
+        let mut deserializer = self.deserializer_from_slice(bytes);
+        let value = T::deserialize(&mut deserializer).map_err(|err| err.at(0))?;
+        deserializer.end()?;
+        Ok(value)
+    }
+
+    /// Like `deserialize`, but returns the bytes left after the value instead
+    /// of applying the trailing bytes policy.
+    pub fn deserialize_partial<'a, T>(self, bytes: &'a [u8]) -> Result<(T, &'a [u8])>
+    where
+        T: Deserialize<'a>,
+    {
+        let mut deserializer = self.deserializer_from_slice(bytes);
+        let value = T::deserialize(&mut deserializer).map_err(|err| err.at(0))?;
+        Ok((value, deserializer.remaining()))
+    }
+
+    pub fn deserialize_from<R, T>(self, reader: R) -> Result<T>
+    where
+        R: io::Read,
+        T: DeserializeOwned,
+    {
+        let mut deserializer = self.deserializer_from_reader(reader);
+        let value = T::deserialize(&mut deserializer).map_err(|err| err.at(0))?;
+        deserializer.end()?;
+        Ok(value)
+    }
+}
@@ -1,212 +1,360 @@
-use std::io::Cursor;
-use std::io::Read;
-use std::marker::PhantomData;
+use std::borrow::Cow;
+use std::io;
 
 use serde::{
     self,
-    de::{IntoDeserializer, SeqAccess, Visitor},
+    de::{DeserializeOwned, IntoDeserializer},
     Deserialize,
 };
 
+use crate::config::{CharEncoding, Endian, IntEncoding, Limits, Options, TrailingBytes};
 use crate::error::{Error, Result};
-
-pub struct Deserializer {
-    pub(crate) reader: Cursor<Vec<u8>>,
+use crate::read::{IoRead, Read, Reference, SliceRead};
+use crate::types::enum_tag::EnumTag;
+use crate::types::zigzag_decode;
+
+pub struct Deserializer<R> {
+    pub(crate) reader: R,
+    // Set by an `enum_tag` wrapper for the enum it wraps directly, cleared by
+    // anything else that could contain an enum.
+    tag: Option<EnumTag>,
+    depth: usize,
+    options: Options,
 }
 
-impl Deserializer {
-    fn read_byte(&mut self) -> Result<u8> {
-        let mut buf = [0u8; 1];
-        if self.reader.read_exact(&mut buf).is_ok() {
-            Ok(buf[0])
-        } else {
-            Err(Error::Eof)
+impl<'de, R: Read<'de>> Deserializer<R> {
+    pub fn new(reader: R) -> Self {
+        Deserializer {
+            reader,
+            tag: None,
+            depth: 0,
+            options: Options::default(),
         }
     }
-}
 
-impl<'de, 'a> serde::Deserializer<'de> for &'a mut Deserializer {
-    type Error = Error;
+    pub fn with_options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
 
-    #[inline]
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        Err(Error::Message(
-            "deserialize_any is not supported!".to_string(),
-        ))
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.options.limits = limits;
+        self
     }
 
-    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.read_byte()? {
-            1 => visitor.visit_bool(true),
-            0 => visitor.visit_bool(false),
-            _ => Err(Error::InvalidData),
+    /// Checks the input against the trailing bytes policy once a value has
+    /// been decoded. A reader is only read far enough to find one more byte,
+    /// so `TrailingBytes` counts that single byte instead of the whole rest.
+    pub fn end(&mut self) -> Result<()> {
+        if self.options.trailing_bytes == TrailingBytes::Reject {
+            let offset = self.reader.offset();
+            match self.reader.trailing_bytes()? {
+                0 => {}
+                len => return Err(Error::TrailingBytes(len).at(offset)),
+            }
         }
+        Ok(())
     }
 
-    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        visitor.visit_u8(self.read_byte()?)
+    fn check_bytes(&self, len: usize) -> Result<()> {
+        match self.options.limits.max_bytes {
+            Some(limit) if len > limit.saturating_sub(self.reader.offset()) => {
+                Err(Error::ByteLimitExceeded { limit })
+            }
+            _ => Ok(()),
+        }
     }
 
-    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        visitor.visit_i8(unsafe { std::mem::transmute::<u8, i8>(self.read_byte()?) })
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.check_bytes(buf.len())?;
+        self.reader.read_exact(buf)
     }
 
-    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        let mut buf = [0u8; 2];
-        if self.reader.read_exact(&mut buf).is_ok() {
-            visitor.visit_u16(u16::from_be_bytes(buf))
-        } else {
-            Err(Error::Eof)
+    fn read_bytes(&mut self, len: usize) -> Result<Reference<'de, '_>> {
+        self.check_bytes(len)?;
+        self.reader.read_bytes(len)
+    }
+
+    fn read_to_end(&mut self) -> Result<Reference<'de, '_>> {
+        let limit = self.options.limits.max_bytes;
+        let max = limit.map_or(usize::MAX, |limit| {
+            limit.saturating_sub(self.reader.offset())
+        });
+        // One byte past the limit is enough to tell that the input exceeds it.
+        let bytes = self.reader.read_to_end(max.saturating_add(1))?;
+        match (limit, &bytes) {
+            (Some(limit), Reference::Borrowed(v) | Reference::Copied(v)) if v.len() > max => {
+                Err(Error::ByteLimitExceeded { limit })
+            }
+            _ => Ok(bytes),
         }
     }
 
-    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        let mut buf = [0u8; 2];
-        if self.reader.read_exact(&mut buf).is_ok() {
-            visitor.visit_i16(i16::from_be_bytes(buf))
-        } else {
-            Err(Error::Eof)
+    fn read_byte(&mut self) -> Result<u8> {
+        let mut buf = [0u8; 1];
+        self.read_exact(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    // Reads a LEB128 value that has to fit in `bits` bits, the groups are
+    // least significant first with the high bit marking a continuation.
+    fn read_leb128(&mut self, bits: u32) -> Result<u64> {
+        let mut result = 0u64;
+        let mut shift = 0;
+        loop {
+            let read = self.read_byte()?;
+            let value = (read & 0x7F) as u64;
+            if shift >= bits || (bits - shift < 7 && value >> (bits - shift) != 0) {
+                return Err(Error::VarIntTooLong);
+            }
+            result |= value << shift;
+            if read & 0x80 == 0 {
+                return Ok(result);
+            }
+            shift += 7;
         }
     }
 
-    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        let mut buf = [0u8; 4];
-        if self.reader.read_exact(&mut buf).is_ok() {
-            visitor.visit_u32(u32::from_be_bytes(buf))
-        } else {
-            Err(Error::Eof)
+    fn read_varint(&mut self) -> Result<i32> {
+        Ok(self.read_leb128(32)? as u32 as i32)
+    }
+
+    fn read_varlong(&mut self) -> Result<i64> {
+        Ok(self.read_leb128(64)? as i64)
+    }
+
+    fn read_length(&mut self) -> Result<usize> {
+        let len = self.read_varint()?;
+        if len < 0 {
+            return Err(Error::InvalidData);
         }
+        Ok(len as usize)
     }
 
-    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        let mut buf = [0u8; 4];
-        if self.reader.read_exact(&mut buf).is_ok() {
-            visitor.visit_i32(i32::from_be_bytes(buf))
-        } else {
-            Err(Error::Eof)
+    fn read_string_length(&mut self) -> Result<usize> {
+        let len = self.read_length()?;
+        check_length(len, self.options.limits.max_string_length)?;
+        // A UTF-16 code unit takes at most three bytes in UTF-8.
+        if let Some(limit) = self.options.limits.max_string_utf16_length {
+            check_length(len, Some(limit.saturating_mul(3)))?;
         }
+        Ok(len)
+    }
+
+    fn read_element_count(&mut self) -> Result<usize> {
+        let len = self.read_length()?;
+        check_length(len, self.options.limits.max_elements)?;
+        Ok(len)
+    }
+
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.tag = None;
+        if let Some(limit) = self.options.limits.max_depth {
+            if self.depth >= limit {
+                return Err(Error::DepthLimitExceeded { limit });
+            }
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+}
+
+fn decode_str<'a>(bytes: &'a [u8], options: &Options) -> Result<Cow<'a, str>> {
+    let v = if options.strict_utf8 {
+        Cow::Borrowed(std::str::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)?)
+    } else {
+        String::from_utf8_lossy(bytes)
+    };
+    if let Some(limit) = options.limits.max_string_utf16_length {
+        check_length(crate::types::bounded_string::utf16_length(&v), Some(limit))?;
+    }
+    Ok(v)
+}
+
+fn check_length(length: usize, limit: Option<usize>) -> Result<()> {
+    match limit {
+        Some(limit) if length > limit => Err(Error::LengthLimitExceeded { length, limit }),
+        _ => Ok(()),
+    }
+}
+
+impl<R: io::Read> Deserializer<IoRead<R>> {
+    pub fn from_reader(reader: R) -> Self {
+        Deserializer::new(IoRead::new(reader))
+    }
+}
+
+impl<'de> Deserializer<SliceRead<'de>> {
+    pub fn from_slice(slice: &'de [u8]) -> Self {
+        Deserializer::new(SliceRead::new(slice))
     }
 
-    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
+    pub fn remaining(&self) -> &'de [u8] {
+        self.reader.remaining()
+    }
+}
+
+macro_rules! deserialize_fixed {
+    ($self:ident, $ty:ty) => {{
+        let mut buf = [0u8; std::mem::size_of::<$ty>()];
+        $self.read_exact(&mut buf)?;
+        match $self.options.endian {
+            Endian::Big => <$ty>::from_be_bytes(buf),
+            Endian::Little => <$ty>::from_le_bytes(buf),
+        }
+    }};
+}
+
+macro_rules! deserialize_int {
+    ($name:ident, $visit:ident, $ty:ty, $unsigned:ty) => {
+        fn $name<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: serde::de::Visitor<'de>,
+        {
+            let v = match self.options.int_encoding {
+                IntEncoding::Fixed => deserialize_fixed!(self, $ty),
+                // Unsigned integers have nothing to ZigZag.
+                IntEncoding::ZigZag if <$ty>::MIN != 0 => {
+                    zigzag_decode(self.read_leb128(<$ty>::BITS)?) as $ty
+                }
+                IntEncoding::VarInt | IntEncoding::ZigZag => {
+                    self.read_leb128(<$ty>::BITS)? as $unsigned as $ty
+                }
+            };
+            visitor.$visit(v)
+        }
+    };
+}
+
+macro_rules! deserialize_float {
+    ($name:ident, $visit:ident, $ty:ty) => {
+        fn $name<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: serde::de::Visitor<'de>,
+        {
+            visitor.$visit(deserialize_fixed!(self, $ty))
+        }
+    };
+}
+
+impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
+    type Error = Error;
+
+    #[inline]
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        let mut buf = [0u8; 8];
-        if self.reader.read_exact(&mut buf).is_ok() {
-            visitor.visit_u64(u64::from_be_bytes(buf))
-        } else {
-            Err(Error::Eof)
-        }
+        Err(Error::Message(
+            "deserialize_any is not supported!".to_string(),
+        ))
     }
 
-    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        let mut buf = [0u8; 8];
-        if self.reader.read_exact(&mut buf).is_ok() {
-            visitor.visit_i64(i64::from_be_bytes(buf))
-        } else {
-            Err(Error::Eof)
+        match self.read_byte()? {
+            1 => visitor.visit_bool(true),
+            0 => visitor.visit_bool(false),
+            v => Err(Error::InvalidBool(v)),
         }
     }
 
-    fn deserialize_u128<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(Error::Unimplemented)
+        visitor.visit_u8(self.read_byte()?)
     }
 
-    fn deserialize_i128<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(Error::Unimplemented)
+        visitor.visit_i8(self.read_byte()? as i8)
     }
 
-    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
+    deserialize_int!(deserialize_u16, visit_u16, u16, u16);
+    deserialize_int!(deserialize_i16, visit_i16, i16, u16);
+    deserialize_int!(deserialize_u32, visit_u32, u32, u32);
+    deserialize_int!(deserialize_i32, visit_i32, i32, u32);
+    deserialize_int!(deserialize_u64, visit_u64, u64, u64);
+    deserialize_int!(deserialize_i64, visit_i64, i64, u64);
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        let mut buf = [0u8; 4];
-        if self.reader.read_exact(&mut buf).is_ok() {
-            visitor.visit_f32(f32::from_be_bytes(buf))
-        } else {
-            Err(Error::Eof)
-        }
+        visitor.visit_u128(deserialize_fixed!(self, u128))
     }
 
-    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        let mut buf = [0u8; 8];
-        if self.reader.read_exact(&mut buf).is_ok() {
-            visitor.visit_f64(f64::from_be_bytes(buf))
-        } else {
-            Err(Error::Eof)
-        }
+        visitor.visit_i128(deserialize_fixed!(self, i128))
     }
 
+    deserialize_float!(deserialize_f32, visit_f32, f32);
+    deserialize_float!(deserialize_f64, visit_f64, f64);
+
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_char(self.read_byte()? as char)
+        let v = match self.options.char_encoding {
+            CharEncoding::Utf8 => {
+                let len = self.read_length()?;
+                if len > 4 {
+                    return Err(Error::InvalidData);
+                }
+                let mut buf = [0u8; 4];
+                self.read_exact(&mut buf[..len])?;
+                let v = std::str::from_utf8(&buf[..len]).map_err(|_| Error::InvalidUtf8)?;
+                let mut chars = v.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(v), None) => v,
+                    _ => return Err(Error::InvalidData),
+                }
+            }
+            CharEncoding::Utf16 => {
+                let unit = deserialize_fixed!(self, u16) as u32;
+                std::char::from_u32(unit).ok_or(Error::InvalidChar(unit))?
+            }
+        };
+        visitor.visit_char(v)
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        let varint = self.deserialize_tuple(999, crate::types::varint::VarIntVisitor)?;
-        let mut buf = vec![];
-        self.reader
-            .by_ref()
-            .take(varint as u64)
-            .read_to_end(&mut buf)
-            .unwrap();
-
-        visitor.visit_string(String::from_utf8_lossy(&buf).to_string())
+        self.deserialize_str(visitor)
     }
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        todo!()
+        let len = self.read_element_count()?;
+        match self.read_bytes(len)? {
+            Reference::Borrowed(bytes) => visitor.visit_borrowed_bytes(bytes),
+            Reference::Copied(bytes) => visitor.visit_bytes(bytes),
+        }
     }
 
-    fn deserialize_option<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(Error::Unimplemented)
+        match self.read_byte()? {
+            1 => self.nested(|deserializer| visitor.visit_some(deserializer)),
+            0 => visitor.visit_none(),
+            v => Err(Error::InvalidBool(v)),
+        }
     }
 
     fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
@@ -216,7 +364,7 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut Deserializer {
         visitor.visit_unit()
     }
 
-    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
@@ -225,104 +373,119 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut Deserializer {
 
     fn deserialize_enum<V>(
         self,
-        name: &'static str,
+        _name: &'static str,
         variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        println!("{:?}", name);
-        println!("{:?}", variants);
-
-        impl<'de, 'a> serde::de::EnumAccess<'de> for &'a mut Deserializer {
-            type Error = Error;
-            type Variant = Self;
-
-            fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
-            where
-                V: serde::de::DeserializeSeed<'de>,
-            {
-                use serde::Deserializer;
-
-                let idx = self.deserialize_tuple(999, crate::types::varint::VarIntVisitor)? as u64;
-                let val: Result<_> = seed.deserialize(idx.into_deserializer());
-                Ok((val?, self))
-            }
-        }
-
-        visitor.visit_enum(self)
-        //Err(Error::Unimplemented)
+        let tag = self.tag.take().unwrap_or(EnumTag::VarInt);
+        self.nested(|deserializer| {
+            visitor.visit_enum(Enum {
+                deserializer,
+                variants,
+                tag,
+            })
+        })
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        if let Some(tag) = EnumTag::from_name(name) {
+            self.tag = Some(tag);
+            let result = visitor.visit_newtype_struct(&mut *self);
+            self.tag = None;
+            return result;
+        }
+        if name == crate::types::uuid::NAME {
+            let options = self.options;
+            self.options.int_encoding = IntEncoding::Fixed;
+            let result = self.nested(|deserializer| visitor.visit_newtype_struct(deserializer));
+            self.options = options;
+            return result;
+        }
+        self.nested(|deserializer| visitor.visit_newtype_struct(deserializer))
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(Error::Unimplemented)
+        let len = self.read_element_count()?;
+        self.deserialize_tuple(len, visitor)
     }
 
     fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        struct Access<'a> {
-            deserializer: &'a mut Deserializer,
-            len: usize,
-        }
-
-        impl<'de, 'a, 'b: 'a> serde::de::SeqAccess<'de> for Access<'a> {
-            type Error = Error;
-
-            fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
-            where
-                T: serde::de::DeserializeSeed<'de>,
-            {
-                if self.len > 0 {
-                    self.len -= 1;
-                    let value =
-                        serde::de::DeserializeSeed::deserialize(seed, &mut *self.deserializer)?;
-                    Ok(Some(value))
-                } else {
-                    Ok(None)
-                }
-            }
-
-            fn size_hint(&self) -> Option<usize> {
-                Some(self.len)
-            }
-        }
-
-        visitor.visit_seq(Access {
-            deserializer: self,
-            len,
-        })
+        self.nested(|deserializer| visitor.visit_seq(Access::new(deserializer, len, None)))
     }
 
+    // Wrapper types in `types` call this with a reserved `$basalt_serde::`
+    // name to have the value decoded natively here, e.g. a VarInt read
+    // straight from the input. Other deserializers don't know the names and
+    // treat them as tuples, which the wrappers' visitors accept as a fallback,
+    // except `BoundedString`, which only has a fallback for human-readable
+    // formats.
     fn deserialize_tuple_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         len: usize,
         visitor: V,
     ) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        self.deserialize_tuple(len, visitor)
+        match name {
+            crate::types::varint::NAME => visitor.visit_i32(self.read_varint()?),
+            crate::types::varlong::NAME => visitor.visit_i64(self.read_varlong()?),
+            crate::types::zigzag_varint::NAME => {
+                visitor.visit_i32(zigzag_decode(self.read_leb128(32)?) as i32)
+            }
+            crate::types::zigzag_varlong::NAME => {
+                visitor.visit_i64(zigzag_decode(self.read_leb128(64)?))
+            }
+            crate::types::remaining_bytes::NAME => match self.read_to_end()? {
+                Reference::Borrowed(bytes) => visitor.visit_borrowed_bytes(bytes),
+                Reference::Copied(bytes) => visitor.visit_bytes(bytes),
+            },
+            crate::types::skip::REST => {
+                match self.options.limits.max_bytes {
+                    Some(_) => self.read_to_end().map(drop)?,
+                    None => self.reader.skip_to_end().map(drop)?,
+                }
+                visitor.visit_unit()
+            }
+            // The UTF-16 limit of a `BoundedString` comes in as `len`, so the
+            // string is rejected before it is read if it can't fit.
+            crate::types::bounded_string::NAME => {
+                let limits = self.options.limits;
+                let limit = limits.max_string_utf16_length.map_or(len, |v| v.min(len));
+                self.options.limits.max_string_utf16_length = Some(limit);
+                let result = serde::Deserializer::deserialize_str(&mut *self, visitor);
+                self.options.limits = limits;
+                result
+            }
+            crate::types::vec_unsized::NAME => {
+                self.nested(|deserializer| visitor.visit_seq(Access::until_end(deserializer)))
+            }
+            crate::types::varu32::NAME => visitor.visit_u32(self.read_leb128(32)? as u32),
+            crate::types::varu64::NAME => visitor.visit_u64(self.read_leb128(64)?),
+            crate::types::varusize::NAME => visitor.visit_u64(self.read_leb128(usize::BITS)?),
+            _ => self.deserialize_tuple(len, visitor),
+        }
     }
 
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        todo!()
+        let len = self.read_element_count()?;
+        self.nested(|deserializer| visitor.visit_map(Access::new(deserializer, len, None)))
     }
 
     fn deserialize_struct<V>(
@@ -334,21 +497,39 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut Deserializer {
     where
         V: serde::de::Visitor<'de>,
     {
-        self.deserialize_tuple(fields.len(), visitor)
+        let root = self.depth == 0;
+        let result = self.nested(|deserializer| {
+            visitor.visit_seq(Access::new(deserializer, fields.len(), Some(fields)))
+        });
+        match result {
+            Err(err) if root => Err(err.within(name)),
+            result => result,
+        }
     }
 
-    fn deserialize_str<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(Error::Unimplemented)
+        let len = self.read_string_length()?;
+        let options = self.options;
+        match self.read_bytes(len)? {
+            Reference::Borrowed(bytes) => match decode_str(bytes, &options)? {
+                Cow::Borrowed(v) => visitor.visit_borrowed_str(v),
+                Cow::Owned(v) => visitor.visit_string(v),
+            },
+            Reference::Copied(bytes) => match decode_str(bytes, &options)? {
+                Cow::Borrowed(v) => visitor.visit_str(v),
+                Cow::Owned(v) => visitor.visit_string(v),
+            },
+        }
     }
 
-    fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(Error::Unimplemented)
+        self.deserialize_bytes(visitor)
     }
 
     fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value>
@@ -358,25 +539,213 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut Deserializer {
         Err(Error::Unimplemented)
     }
 
+    // Without a size hint from `types::skip` there is no telling where an
+    // ignored value ends.
     fn deserialize_ignored_any<V>(self, _visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(Error::Unimplemented)
+        Err(Error::CannotSkip)
     }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+pub fn from_bytes<'a, T>(data: &'a [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::from_slice(data);
+    T::deserialize(&mut deserializer).map_err(|err| err.at(0))
 }
 
-pub fn from_bytes<'a, T>(data: &[u8]) -> Result<T>
+/// Decodes a value from the start of `data` and returns it together with the
+/// bytes that follow it.
+pub fn from_bytes_partial<'a, T>(data: &'a [u8]) -> Result<(T, &'a [u8])>
 where
     T: Deserialize<'a>,
 {
-    let mut deserializer = Deserializer {
-        reader: ::std::io::Cursor::new(data.to_vec()),
+    Options::new().deserialize_partial(data)
+}
+
+pub fn from_reader<R, T>(reader: R) -> Result<T>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    let mut deserializer = Deserializer::from_reader(reader);
+    T::deserialize(&mut deserializer).map_err(|err| err.at(0))
+}
+
+// Shared by sequences, tuples, structs and maps. Errors raised while decoding
+// an element are tagged with the element's offset and its field name or index.
+struct Access<'a, R> {
+    deserializer: &'a mut Deserializer<R>,
+    len: usize,
+    index: usize,
+    fields: Option<&'static [&'static str]>,
+    // Elements run to the end of the input instead of for `len`.
+    until_end: bool,
+}
+
+impl<'a, 'de, R: Read<'de>> Access<'a, R> {
+    fn new(
+        deserializer: &'a mut Deserializer<R>,
+        len: usize,
+        fields: Option<&'static [&'static str]>,
+    ) -> Self {
+        Access {
+            deserializer,
+            len,
+            index: 0,
+            fields,
+            until_end: false,
+        }
+    }
+
+    fn until_end(deserializer: &'a mut Deserializer<R>) -> Self {
+        Access {
+            until_end: true,
+            ..Access::new(deserializer, usize::MAX, None)
+        }
+    }
+
+    fn element<T>(&mut self, seed: T) -> Result<T::Value>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        let offset = self.deserializer.reader.offset();
+        seed.deserialize(&mut *self.deserializer).map_err(|err| {
+            match self.fields.and_then(|f| f.get(self.index)) {
+                Some(field) => err.at(offset).within(&format!(".{}", field)),
+                None => err.at(offset).within(&format!("[{}]", self.index)),
+            }
+        })
+    }
+}
+
+impl<'de, R: Read<'de>> serde::de::SeqAccess<'de> for Access<'_, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        if self.until_end {
+            if self.deserializer.reader.is_at_end()? {
+                return Ok(None);
+            }
+            check_length(
+                self.index + 1,
+                self.deserializer.options.limits.max_elements,
+            )?;
+        }
+        if self.len > 0 {
+            self.len -= 1;
+            let value = self.element(seed)?;
+            self.index += 1;
+            Ok(Some(value))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        match self.until_end {
+            true => None,
+            false => Some(self.len),
+        }
+    }
+}
+
+impl<'de, R: Read<'de>> serde::de::MapAccess<'de> for Access<'_, R> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: serde::de::DeserializeSeed<'de>,
+    {
+        if self.len > 0 {
+            self.len -= 1;
+            self.element(seed).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        let value = self.element(seed)?;
+        self.index += 1;
+        Ok(value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+struct Enum<'a, R> {
+    deserializer: &'a mut Deserializer<R>,
+    variants: &'static [&'static str],
+    tag: EnumTag,
+}
+
+impl<'de, 'a, R: Read<'de>> serde::de::EnumAccess<'de> for Enum<'a, R> {
+    type Error = Error;
+    type Variant = &'a mut Deserializer<R>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        let Enum {
+            deserializer,
+            variants,
+            tag,
+        } = self;
+        let id = match tag {
+            EnumTag::VarInt => deserializer.read_varint()?,
+            EnumTag::U8 => deserializer.read_byte()? as i32,
+            EnumTag::I32 => deserialize_fixed!(deserializer, i32),
+            EnumTag::String => {
+                let len = deserializer.read_string_length()?;
+                let name = match deserializer.read_bytes(len)? {
+                    Reference::Borrowed(bytes) | Reference::Copied(bytes) => {
+                        std::str::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)?
+                    }
+                };
+                let val: Result<_> = seed.deserialize(name.into_deserializer());
+                return Ok((val?, deserializer));
+            }
+        };
+        let idx = variant_index(variants, id)?;
+        let val: Result<_> = seed.deserialize(idx.into_deserializer());
+        Ok((val?, deserializer))
+    }
+}
+
+// Mirrors `Serializer::write_variant`: a variant renamed to an integer claims
+// that discriminant, every other variant is identified by its position. An
+// ID claimed both ways can't be told apart and is rejected.
+fn variant_index(variants: &[&str], id: i32) -> Result<u64> {
+    let renamed = variants.iter().position(|v| v.parse::<i32>() == Ok(id));
+    let positional = match variants.get(id as usize) {
+        Some(v) if id >= 0 && v.parse::<i32>().is_err() => Some(id as usize),
+        _ => None,
     };
-    T::deserialize(&mut deserializer)
+    match (renamed, positional) {
+        (Some(_), Some(_)) => Err(Error::AmbiguousVariant(id)),
+        (Some(idx), None) | (None, Some(idx)) => Ok(idx as u64),
+        (None, None) => Err(Error::InvalidData),
+    }
 }
 
-impl<'de, 'a> serde::de::VariantAccess<'de> for &'a mut Deserializer {
+impl<'de, R: Read<'de>> serde::de::VariantAccess<'de> for &mut Deserializer<R> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
@@ -401,6 +770,8 @@ impl<'de, 'a> serde::de::VariantAccess<'de> for &'a mut Deserializer {
     where
         V: serde::de::Visitor<'de>,
     {
-        serde::de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+        self.nested(|deserializer| {
+            visitor.visit_seq(Access::new(deserializer, fields.len(), Some(fields)))
+        })
     }
 }
@@ -1,4 +1,5 @@
 use ::std::fmt::{self, Display};
+use ::std::io;
 
 use ::serde::{de, ser};
 
@@ -10,6 +11,93 @@ pub enum Error {
     InvalidData,
     Unimplemented,
     Eof,
+    Io(String),
+    InvalidBool(u8),
+    VarIntTooLong,
+    InvalidUtf8,
+    InvalidChar(u32),
+    LengthLimitExceeded {
+        length: usize,
+        limit: usize,
+    },
+    ByteLimitExceeded {
+        limit: usize,
+    },
+    DepthLimitExceeded {
+        limit: usize,
+    },
+    TrailingBytes(usize),
+    CannotSkip,
+    AmbiguousVariant(i32),
+    At {
+        offset: usize,
+        path: String,
+        error: Box<Error>,
+    },
+}
+
+impl Error {
+    /// The error without the position it was raised at.
+    pub fn inner(&self) -> &Error {
+        match self {
+            Error::At { error, .. } => error,
+            error => error,
+        }
+    }
+
+    /// Byte offset of the value that failed to decode.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Error::At { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Path to the value that failed to decode, e.g. `JoinGame.dimension_codec[3].name`.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Error::At { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn at(self, offset: usize) -> Error {
+        match self {
+            Error::At { .. } => self,
+            error => Error::At {
+                offset,
+                path: String::new(),
+                error: Box::new(error),
+            },
+        }
+    }
+
+    pub(crate) fn within(self, segment: &str) -> Error {
+        match self {
+            Error::At {
+                offset,
+                mut path,
+                error,
+            } => {
+                path.insert_str(0, segment);
+                Error::At {
+                    offset,
+                    path,
+                    error,
+                }
+            }
+            error => error,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => Error::Eof,
+            _ => Error::Io(err.to_string()),
+        }
+    }
 }
 
 impl ser::Error for Error {
@@ -31,8 +119,49 @@ impl Display for Error {
             Error::InvalidData => formatter.write_str("invalid data was received"),
             Error::Unimplemented => formatter.write_str("the datatype is not implemented"),
             Error::Eof => formatter.write_str("unexpected end of input"),
+            Error::Io(msg) => write!(formatter, "i/o error: {}", msg),
+            Error::InvalidBool(v) => write!(formatter, "invalid bool value {:#04x}", v),
+            Error::VarIntTooLong => formatter.write_str("VarInt is too long"),
+            Error::InvalidUtf8 => formatter.write_str("string is not valid UTF-8"),
+            Error::InvalidChar(v) => write!(formatter, "invalid char {:#06x}", v),
+            Error::LengthLimitExceeded { length, limit } => write!(
+                formatter,
+                "length {} exceeds the limit of {}",
+                length, limit
+            ),
+            Error::ByteLimitExceeded { limit } => {
+                write!(formatter, "input exceeds the limit of {} bytes", limit)
+            }
+            Error::DepthLimitExceeded { limit } => {
+                write!(formatter, "nesting exceeds the depth limit of {}", limit)
+            }
+            Error::TrailingBytes(len) => write!(formatter, "{} unconsumed trailing bytes", len),
+            Error::AmbiguousVariant(id) => write!(
+                formatter,
+                "variant ID {} is claimed by both a renamed and a positional variant",
+                id
+            ),
+            Error::CannotSkip => formatter
+                .write_str("cannot skip a value of unknown size, use a `types::skip` type instead"),
+            Error::At {
+                offset,
+                path,
+                error,
+            } if path.is_empty() => write!(formatter, "{} at byte {}", error, offset),
+            Error::At {
+                offset,
+                path,
+                error,
+            } => write!(formatter, "{} in {} at byte {}", error, path, offset),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::At { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -1,18 +1,30 @@
-#![feature(arbitrary_enum_discriminant)]
-
+mod config;
 mod de;
 mod error;
+mod read;
 mod ser;
 
 pub mod types;
 
-pub use de::{from_bytes, Deserializer};
+pub use config::{
+    CharEncoding, Endian, IntEncoding, Limits, Options, TrailingBytes, DEFAULT_MAX_DEPTH,
+};
+pub use de::{from_bytes, from_bytes_partial, from_reader, Deserializer};
 pub use error::{Error, Result};
-pub use ser::{to_bytes, Serializer};
+pub use read::{IoRead, Read, Reference, SliceRead};
+pub use ser::{serialized_size, to_bytes, to_bytes_into, to_writer, Serializer};
 
 #[cfg(test)]
 mod test {
-    use crate::types::{varint, varlong};
+    use crate::types::{
+        uuid::Uuid,
+        varint::{self, VarInt},
+        varlong::{self, VarLong},
+        varshort::VarShort,
+        varu32::VarU32,
+        varu64::VarU64,
+        varusize::VarUsize,
+    };
     use serde::{Deserialize, Serialize};
 
     #[derive(Debug, Deserialize, Serialize)]
@@ -27,6 +39,14 @@ mod test {
         my_varlong: i64,
     }
 
+    #[derive(Debug, Deserialize, Serialize)]
+    struct MyZigZag {
+        #[serde(with = "crate::types::zigzag_varint")]
+        my_varint: i32,
+        #[serde(with = "crate::types::zigzag_varlong")]
+        my_varlong: i64,
+    }
+
     #[derive(Debug, Deserialize, Serialize)]
     #[repr(u32)]
     enum MyVarintEnum {
@@ -40,14 +60,17 @@ mod test {
         my_enum: MyVarintEnum,
     }
 
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
     #[repr(u8)]
     enum Chunk {
+        #[serde(rename = "1")]
         Full {
             primary_bitmask: i32,
             heightmaps: String,
-            biomes: Vec<i32>,
+            biomes: Vec<VarInt>,
             size: i32,
         } = 1,
+        #[serde(rename = "0")]
         Partial {
             primary_bitmask: i32,
             heightmaps: String,
@@ -55,6 +78,7 @@ mod test {
         } = 0,
     }
 
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
     struct ChunkData {
         chunk_x: i32,
         chunk_z: i32,
@@ -63,11 +87,7 @@ mod test {
 
     #[test]
     fn varint_enum() {
-        let tests: [&[u8]; 3] = [
-            &[0x00],
-            &[0x01, 0x4, 'T' as u8, 'E' as u8, 'S' as u8, 'T' as u8],
-            &[0x02],
-        ];
+        let tests: [&[u8]; 3] = [&[0x00], &[0x01, 0x4, b'T', b'E', b'S', b'T'], &[0x02]];
 
         for test in &tests {
             let deserialized: MyContainer = crate::de::from_bytes(test).unwrap();
@@ -78,6 +98,195 @@ mod test {
         }
     }
 
+    #[test]
+    fn explicit_discriminant() {
+        let full = ChunkData {
+            chunk_x: 1,
+            chunk_z: -1,
+            chunk: Chunk::Full {
+                primary_bitmask: 3,
+                heightmaps: "H".to_string(),
+                biomes: vec![VarInt(127), VarInt(-1)],
+                size: 0,
+            },
+        };
+        let partial = ChunkData {
+            chunk_x: 1,
+            chunk_z: -1,
+            chunk: Chunk::Partial {
+                primary_bitmask: 3,
+                heightmaps: "H".to_string(),
+                size: 0,
+            },
+        };
+        let tests: [(ChunkData, &[u8]); 2] = [
+            (
+                full,
+                &[
+                    0, 0, 0, 1, 0xff, 0xff, 0xff, 0xff, 0x01, 0, 0, 0, 3, 0x01, b'H', 0x02, 0x7f,
+                    0xff, 0xff, 0xff, 0xff, 0x0f, 0, 0, 0, 0,
+                ],
+            ),
+            (
+                partial,
+                &[
+                    0, 0, 0, 1, 0xff, 0xff, 0xff, 0xff, 0x00, 0, 0, 0, 3, 0x01, b'H', 0, 0, 0, 0,
+                ],
+            ),
+        ];
+
+        for (goal, bytes) in &tests {
+            let deserialized: ChunkData = crate::de::from_bytes(bytes).unwrap();
+            assert_eq!(goal, &deserialized);
+
+            let serialized = crate::ser::to_bytes(goal).unwrap();
+            assert_eq!(bytes.to_vec(), serialized.as_slice());
+        }
+
+        let unknown: crate::Result<Chunk> = crate::de::from_bytes(&[0x02]);
+        assert_eq!(&crate::Error::InvalidData, unknown.unwrap_err().inner());
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    enum Clashing {
+        A,
+        #[serde(rename = "0")]
+        B,
+        C,
+    }
+
+    #[test]
+    fn ambiguous_discriminant() {
+        let err = crate::de::from_bytes::<Clashing>(&[0x00]).unwrap_err();
+        assert_eq!(&crate::Error::AmbiguousVariant(0), err.inner());
+        assert_eq!(Clashing::C, crate::de::from_bytes(&[0x02]).unwrap());
+        let unknown: crate::Result<Clashing> = crate::de::from_bytes(&[0x01]);
+        assert_eq!(&crate::Error::InvalidData, unknown.unwrap_err().inner());
+
+        // Both `A` and `B` are written as 0, so neither can be read back.
+        assert_eq!(
+            crate::ser::to_bytes(&Clashing::A).unwrap(),
+            crate::ser::to_bytes(&Clashing::B).unwrap()
+        );
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    enum Gamemode {
+        Survival,
+        Creative,
+        #[serde(rename = "3")]
+        Spectator,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    enum Action {
+        #[serde(rename = "-1")]
+        Reset,
+        Set {
+            value: u8,
+        },
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    enum Dimension {
+        #[serde(rename = "minecraft:overworld")]
+        Overworld,
+        #[serde(rename = "minecraft:the_nether")]
+        Nether,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct MyTaggedEnums {
+        #[serde(with = "crate::types::enum_tag::u8")]
+        gamemode: Gamemode,
+        #[serde(with = "crate::types::enum_tag::i32")]
+        action: Action,
+        #[serde(with = "crate::types::enum_tag::string")]
+        dimension: Dimension,
+        #[serde(with = "crate::types::enum_tag::varint")]
+        previous_gamemode: Gamemode,
+        default_gamemode: Gamemode,
+    }
+
+    #[test]
+    fn enum_tag() {
+        let tests: [(MyTaggedEnums, &[u8]); 2] = [
+            (
+                MyTaggedEnums {
+                    gamemode: Gamemode::Spectator,
+                    action: Action::Reset,
+                    dimension: Dimension::Nether,
+                    previous_gamemode: Gamemode::Creative,
+                    default_gamemode: Gamemode::Survival,
+                },
+                &[
+                    0x03, 0xff, 0xff, 0xff, 0xff, 0x14, b'm', b'i', b'n', b'e', b'c', b'r', b'a',
+                    b'f', b't', b':', b't', b'h', b'e', b'_', b'n', b'e', b't', b'h', b'e', b'r',
+                    0x01, 0x00,
+                ],
+            ),
+            (
+                MyTaggedEnums {
+                    gamemode: Gamemode::Creative,
+                    action: Action::Set { value: 7 },
+                    dimension: Dimension::Overworld,
+                    previous_gamemode: Gamemode::Spectator,
+                    default_gamemode: Gamemode::Spectator,
+                },
+                &[
+                    0x01, 0, 0, 0, 1, 7, 0x13, b'm', b'i', b'n', b'e', b'c', b'r', b'a', b'f',
+                    b't', b':', b'o', b'v', b'e', b'r', b'w', b'o', b'r', b'l', b'd', 0x03, 0x03,
+                ],
+            ),
+        ];
+
+        for (goal, bytes) in &tests {
+            let deserialized: MyTaggedEnums = crate::de::from_bytes(bytes).unwrap();
+            assert_eq!(goal, &deserialized);
+
+            let serialized = crate::ser::to_bytes(goal).unwrap();
+            assert_eq!(bytes.to_vec(), serialized.as_slice());
+        }
+
+        // Like any other fixed-width int, an `i32` tag follows the endianness.
+        let little = crate::Options::new().with_little_endian();
+        let goal = &tests[1].0;
+        let serialized = little.serialize(goal).unwrap();
+        assert_eq!(&[0x01, 1, 0, 0, 0, 7], &serialized[..6]);
+        assert_eq!(
+            goal,
+            &little.deserialize::<MyTaggedEnums>(&serialized).unwrap()
+        );
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct TaggedContainers {
+        #[serde(with = "crate::types::enum_tag::i32")]
+        modes: Vec<Gamemode>,
+        #[serde(with = "crate::types::enum_tag::i32")]
+        mode: Option<Gamemode>,
+        #[serde(with = "crate::types::enum_tag::i32")]
+        action: Action,
+    }
+
+    #[test]
+    fn enum_tag_scope() {
+        // The wrapper tags the enum it wraps, not enums nested inside other
+        // values, which keep the default VarInt tag.
+        let value = TaggedContainers {
+            modes: vec![Gamemode::Spectator, Gamemode::Creative],
+            mode: Some(Gamemode::Spectator),
+            action: Action::Reset,
+        };
+        let bytes: &[u8] = &[0x02, 0x03, 0x01, 0x01, 0x03, 0xff, 0xff, 0xff, 0xff];
+
+        let deserialized: TaggedContainers = crate::de::from_bytes(bytes).unwrap();
+        assert_eq!(value, deserialized);
+
+        let serialized = crate::ser::to_bytes(&value).unwrap();
+        assert_eq!(bytes.to_vec(), serialized.as_slice());
+    }
+
     #[test]
     fn varint() {
         let tests: [(i32, &[u8]); 10] = [
@@ -138,4 +347,994 @@ mod test {
             assert_eq!(bytes.to_vec(), serialized.as_slice());
         }
     }
+
+    #[test]
+    fn zigzag() {
+        let tests: [(i32, i64, &[u8]); 8] = [
+            (0, 0, &[0x00, 0x00]),
+            (-1, -1, &[0x01, 0x01]),
+            (1, 1, &[0x02, 0x02]),
+            (-2, -2, &[0x03, 0x03]),
+            (-64, 64, &[0x7f, 0x80, 0x01]),
+            (
+                2147483647,
+                2147483648,
+                &[0xfe, 0xff, 0xff, 0xff, 0x0f, 0x80, 0x80, 0x80, 0x80, 0x10],
+            ),
+            (
+                -2147483648,
+                9223372036854775807,
+                &[
+                    0xff, 0xff, 0xff, 0xff, 0x0f, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                    0xff, 0x01,
+                ],
+            ),
+            (
+                -2147483648,
+                -9223372036854775808,
+                &[
+                    0xff, 0xff, 0xff, 0xff, 0x0f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                    0xff, 0x01,
+                ],
+            ),
+        ];
+
+        for (varint, varlong, bytes) in &tests {
+            let deserialized: MyZigZag = crate::de::from_bytes(bytes).unwrap();
+            assert_eq!(varint, &deserialized.my_varint);
+            assert_eq!(varlong, &deserialized.my_varlong);
+
+            let serialized = crate::ser::to_bytes(&MyZigZag {
+                my_varint: *varint,
+                my_varlong: *varlong,
+            })
+            .unwrap();
+            assert_eq!(bytes.to_vec(), serialized.as_slice());
+        }
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct MyOption {
+        #[serde(with = "varint")]
+        entity_id: i32,
+        name: Option<String>,
+        position: Option<(f64, f64, f64)>,
+    }
+
+    #[test]
+    fn option() {
+        let tests: [(MyOption, &[u8]); 3] = [
+            (
+                MyOption {
+                    entity_id: 1,
+                    name: None,
+                    position: None,
+                },
+                &[0x01, 0x00, 0x00],
+            ),
+            (
+                MyOption {
+                    entity_id: 2,
+                    name: Some("TEST".to_string()),
+                    position: None,
+                },
+                &[0x02, 0x01, 0x04, b'T', b'E', b'S', b'T', 0x00],
+            ),
+            (
+                MyOption {
+                    entity_id: 3,
+                    name: None,
+                    position: Some((1.0, -2.0, 0.5)),
+                },
+                &[
+                    0x03, 0x00, 0x01, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0, 0xc0, 0x00, 0, 0, 0, 0, 0, 0,
+                    0x3f, 0xe0, 0, 0, 0, 0, 0, 0,
+                ],
+            ),
+        ];
+
+        for (goal, bytes) in &tests {
+            let deserialized: MyOption = crate::de::from_bytes(bytes).unwrap();
+            assert_eq!(goal, &deserialized);
+
+            let serialized = crate::ser::to_bytes(goal).unwrap();
+            assert_eq!(bytes.to_vec(), serialized.as_slice());
+        }
+
+        let invalid: crate::Result<MyOption> = crate::de::from_bytes(&[0x01, 0x02]);
+        assert_eq!(&crate::Error::InvalidBool(2), invalid.unwrap_err().inner());
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct MyMap {
+        statistics: std::collections::BTreeMap<String, i32>,
+    }
+
+    #[test]
+    fn map() {
+        let mut statistics = std::collections::BTreeMap::new();
+        statistics.insert("A".to_string(), 1);
+        statistics.insert("B".to_string(), 256);
+        let goal = MyMap { statistics };
+        let bytes: &[u8] = &[0x02, 0x01, b'A', 0, 0, 0, 1, 0x01, b'B', 0, 0, 1, 0];
+
+        let deserialized: MyMap = crate::de::from_bytes(bytes).unwrap();
+        assert_eq!(goal, deserialized);
+
+        let serialized = crate::ser::to_bytes(&goal).unwrap();
+        assert_eq!(bytes.to_vec(), serialized.as_slice());
+
+        let hash_map: std::collections::HashMap<String, i32> =
+            crate::de::from_bytes(bytes).unwrap();
+        assert_eq!(Some(&256), hash_map.get("B"));
+
+        let negative: crate::Result<MyMap> = crate::de::from_bytes(&[0xff, 0xff, 0xff, 0xff, 0x0f]);
+        assert_eq!(&crate::Error::InvalidData, negative.unwrap_err().inner());
+
+        let truncated: crate::Result<MyMap> = crate::de::from_bytes(&bytes[..7]);
+        assert_eq!(&crate::Error::Eof, truncated.unwrap_err().inner());
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct MySequences {
+        biomes: Vec<i32>,
+        #[serde(with = "crate::types::vec_sized")]
+        names: Vec<String>,
+        #[serde(with = "crate::types::vec_unsized")]
+        data: Vec<u8>,
+    }
+
+    #[test]
+    fn sequence() {
+        let goal = MySequences {
+            biomes: vec![1, -1],
+            names: vec!["A".to_string()],
+            data: vec![0xca, 0xfe],
+        };
+        let bytes: &[u8] = &[
+            0x02, 0, 0, 0, 1, 0xff, 0xff, 0xff, 0xff, 0x01, 0x01, b'A', 0xca, 0xfe,
+        ];
+
+        let deserialized: MySequences = crate::de::from_bytes(bytes).unwrap();
+        assert_eq!(goal, deserialized);
+
+        let serialized = crate::ser::to_bytes(&goal).unwrap();
+        assert_eq!(bytes.to_vec(), serialized.as_slice());
+
+        let truncated: crate::Result<MySequences> = crate::de::from_bytes(&bytes[..7]);
+        assert_eq!(&crate::Error::Eof, truncated.unwrap_err().inner());
+
+        #[derive(Debug, Deserialize)]
+        #[serde(bound = "T: Deserialize<'de>")]
+        struct Unsized<T> {
+            #[serde(with = "crate::types::vec_unsized")]
+            data: Vec<T>,
+        }
+
+        let partial: &[u8] = &[0, 1, 0, 2, 9];
+        let err = crate::de::from_bytes::<Unsized<u16>>(partial).unwrap_err();
+        assert_eq!(&crate::Error::Eof, err.inner());
+        assert_eq!(Some("Unsized.data[2]"), err.path());
+        let err = crate::de::from_reader::<_, Unsized<u16>>(partial).unwrap_err();
+        assert_eq!(&crate::Error::Eof, err.inner());
+
+        let invalid: &[u8] = &[0x01, b'A', 0x01, 0xff];
+        let err = crate::de::from_bytes::<Unsized<String>>(invalid).unwrap_err();
+        assert_eq!(&crate::Error::InvalidUtf8, err.inner());
+
+        let whole = crate::de::from_reader::<_, Unsized<u16>>(&partial[..4]).unwrap();
+        assert_eq!(vec![1, 2], whole.data);
+    }
+
+    #[test]
+    fn reader() {
+        use std::io::Read;
+
+        let bytes: &[u8] = &[0x01, 0x4, b'T', b'E', b'S', b'T', 0xff];
+        let mut reader = bytes.chain(&[0x01][..]);
+
+        let deserialized: MyContainer = crate::de::from_reader(&mut reader).unwrap();
+        assert!(matches!(
+            deserialized.my_enum,
+            MyVarintEnum::Some { ref my_string } if my_string == "TEST"
+        ));
+
+        let deserialized: MyVarint = crate::de::from_reader(&mut reader).unwrap();
+        assert_eq!(255, deserialized.my_varint);
+
+        let truncated: &[u8] = &[0x01, 0x4, b'T', b'E'];
+        let result: crate::Result<MyContainer> = crate::de::from_reader(truncated);
+        assert_eq!(&crate::Error::Eof, result.unwrap_err().inner());
+    }
+
+    #[test]
+    fn writer() {
+        let value = MyContainer {
+            my_enum: MyVarintEnum::Some {
+                my_string: "TEST".to_string(),
+            },
+        };
+
+        let mut output = vec![0xff];
+        crate::ser::to_writer(&mut output, &value).unwrap();
+        assert_eq!(
+            &[0xff, 0x01, 0x4, b'T', b'E', b'S', b'T'],
+            output.as_slice()
+        );
+
+        let mut buf = [0u8; 3];
+        let result = crate::ser::to_writer(&mut &mut buf[..], &value);
+        assert!(matches!(result, Err(crate::Error::Io(_))));
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct PluginMessage<'a> {
+        channel: &'a str,
+        #[serde(borrow)]
+        data: &'a [u8],
+    }
+
+    #[test]
+    fn borrowed() {
+        let bytes: &[u8] = &[0x04, b'm', b'c', b':', b'a', 0x03, 0x01, 0x02, 0x03];
+
+        let deserialized: PluginMessage = crate::de::from_bytes(bytes).unwrap();
+        assert_eq!("mc:a", deserialized.channel);
+        assert_eq!(&[0x01, 0x02, 0x03], deserialized.data);
+        assert!(std::ptr::eq(&bytes[1], &deserialized.channel.as_bytes()[0]));
+        assert!(std::ptr::eq(&bytes[6], &deserialized.data[0]));
+
+        let result: crate::Result<PluginMessage> = crate::de::from_bytes(&bytes[..7]);
+        assert_eq!(&crate::Error::Eof, result.unwrap_err().inner());
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct DimensionCodec {
+        name: String,
+        natural: bool,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct JoinGame {
+        entity_id: i32,
+        dimension_codec: Vec<DimensionCodec>,
+    }
+
+    #[test]
+    fn error_position() {
+        let bytes: &[u8] = &[0, 0, 0, 1, 0x02, 0x01, b'A', 0x01, 0x01, b'B', 0x02, 0x00];
+
+        let err = crate::de::from_bytes::<JoinGame>(bytes).unwrap_err();
+        assert_eq!(&crate::Error::InvalidBool(2), err.inner());
+        assert_eq!(Some("JoinGame.dimension_codec[1].natural"), err.path());
+        assert_eq!(Some(10), err.offset());
+        assert_eq!(
+            "invalid bool value 0x02 in JoinGame.dimension_codec[1].natural at byte 10",
+            err.to_string()
+        );
+
+        let err = crate::de::from_bytes::<JoinGame>(&bytes[..8]).unwrap_err();
+        assert_eq!(&crate::Error::Eof, err.inner());
+        assert_eq!(Some("JoinGame.dimension_codec[1].name"), err.path());
+
+        let err = crate::de::from_bytes::<MyVarint>(&[0xff; 6]).unwrap_err();
+        assert_eq!(&crate::Error::VarIntTooLong, err.inner());
+        assert_eq!(Some("MyVarint.my_varint"), err.path());
+
+        let err = crate::de::from_reader::<_, JoinGame>(bytes).unwrap_err();
+        assert_eq!(Some(10), err.offset());
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Node {
+        next: Option<Box<Node>>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct NewtypeNode(Option<Box<NewtypeNode>>);
+
+    #[test]
+    fn limits() {
+        use serde::Deserialize;
+
+        let limits = crate::Limits {
+            max_string_length: Some(4),
+            max_string_utf16_length: None,
+            max_elements: Some(2),
+            max_bytes: Some(16),
+            max_depth: Some(3),
+        };
+        let decode = |bytes: &[u8]| {
+            let mut deserializer = crate::Deserializer::from_reader(bytes).with_limits(limits);
+            JoinGame::deserialize(&mut deserializer).map_err(|err| err.inner().clone())
+        };
+
+        assert!(decode(&[0, 0, 0, 1, 0x01, 0x04, b'A', b'B', b'C', b'D', 0x00]).is_ok());
+        assert_eq!(
+            Err(crate::Error::LengthLimitExceeded {
+                length: i32::MAX as usize,
+                limit: 4
+            }),
+            decode(&[0, 0, 0, 1, 0x01, 0xff, 0xff, 0xff, 0xff, 0x07])
+        );
+        assert_eq!(
+            Err(crate::Error::LengthLimitExceeded {
+                length: 3,
+                limit: 2
+            }),
+            decode(&[0, 0, 0, 1, 0x03])
+        );
+        assert_eq!(
+            Err(crate::Error::ByteLimitExceeded { limit: 16 }),
+            decode(&[
+                0, 0, 0, 1, 0x02, 0x04, b'A', b'B', b'C', b'D', 0x00, 0x04, b'A', b'B', b'C', b'D',
+                0x00
+            ])
+        );
+
+        let nested: &[u8] = &[0x01, 0x01, 0x01, 0x00];
+        let mut deserializer = crate::Deserializer::from_slice(nested).with_limits(limits);
+        let err = Node::deserialize(&mut deserializer).unwrap_err();
+        assert_eq!(&crate::Error::DepthLimitExceeded { limit: 3 }, err.inner());
+        let node = crate::de::from_bytes::<Node>(nested).unwrap();
+        let leaf = Node { next: None };
+        let goal = (0..3).fold(leaf, |node, _| Node {
+            next: Some(Box::new(node)),
+        });
+        assert_eq!(goal, node);
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct MyIntegers {
+        a: i16,
+        b: u32,
+        c: i64,
+        d: f32,
+        name: String,
+    }
+
+    #[test]
+    fn options() {
+        let value = MyIntegers {
+            a: -2,
+            b: 300,
+            c: -1,
+            d: 1.0,
+            name: "A".to_string(),
+        };
+        let tests: [(crate::Options, &[u8]); 4] = [
+            (
+                crate::Options::new(),
+                &[
+                    0xff, 0xfe, 0, 0, 0x01, 0x2c, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                    0x3f, 0x80, 0, 0, 0x01, b'A',
+                ],
+            ),
+            (
+                crate::Options::new().with_little_endian(),
+                &[
+                    0xfe, 0xff, 0x2c, 0x01, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                    0, 0, 0x80, 0x3f, 0x01, b'A',
+                ],
+            ),
+            (
+                crate::Options::new().with_varint_encoding(),
+                &[
+                    0xfe, 0xff, 0x03, 0xac, 0x02, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                    0xff, 0x01, 0x3f, 0x80, 0, 0, 0x01, b'A',
+                ],
+            ),
+            (
+                crate::Options::new().with_zigzag_encoding(),
+                &[0x03, 0xac, 0x02, 0x01, 0x3f, 0x80, 0, 0, 0x01, b'A'],
+            ),
+        ];
+
+        for (options, bytes) in &tests {
+            let deserialized: MyIntegers = options.deserialize(bytes).unwrap();
+            assert_eq!(value, deserialized);
+
+            let serialized = options.serialize(&value).unwrap();
+            assert_eq!(bytes.to_vec(), serialized.as_slice());
+        }
+
+        let varint = crate::Options::new().with_varint_encoding();
+        let overflow = varint.deserialize::<MyIntegers>(&[0xff, 0xff, 0x04]);
+        assert_eq!(&crate::Error::VarIntTooLong, overflow.unwrap_err().inner());
+
+        let mut bytes = tests[0].1.to_vec();
+        bytes.extend(&[0, 0]);
+        assert!(crate::Options::new()
+            .deserialize::<MyIntegers>(&bytes)
+            .is_ok());
+        let strict = crate::Options::new().reject_trailing_bytes();
+        let err = strict.deserialize::<MyIntegers>(&bytes).unwrap_err();
+        assert_eq!(&crate::Error::TrailingBytes(2), err.inner());
+        assert_eq!(Some(20), err.offset());
+        let err = strict
+            .deserialize_from::<_, MyIntegers>(&bytes[..])
+            .unwrap_err();
+        assert_eq!(&crate::Error::TrailingBytes(1), err.inner());
+
+        let invalid: &[u8] = &[0x02, 0xc3, 0x28];
+        let lossy: String = crate::Options::new()
+            .with_lossy_utf8()
+            .deserialize(invalid)
+            .unwrap();
+        assert_eq!("\u{fffd}(", lossy);
+        let err = crate::Options::new()
+            .deserialize::<String>(invalid)
+            .unwrap_err();
+        assert_eq!(&crate::Error::InvalidUtf8, err.inner());
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct MissingField {
+        entity_id: i32,
+    }
+
+    #[test]
+    fn trailing_bytes() {
+        let bytes: &[u8] = &[0, 0, 0, 1, 0x01, 0x01, b'A', 0x00];
+
+        let (value, rest): (MissingField, _) = crate::de::from_bytes_partial(bytes).unwrap();
+        assert_eq!(MissingField { entity_id: 1 }, value);
+        assert_eq!(&bytes[4..], rest);
+
+        let (value, rest): (Vec<DimensionCodec>, _) = crate::de::from_bytes_partial(rest).unwrap();
+        assert_eq!(1, value.len());
+        assert!(rest.is_empty());
+
+        assert!(crate::de::from_bytes::<MissingField>(bytes).is_ok());
+        let strict = crate::Options::new().reject_trailing_bytes();
+        let err = strict.deserialize::<MissingField>(bytes).unwrap_err();
+        assert_eq!(&crate::Error::TrailingBytes(4), err.inner());
+        assert!(strict.deserialize::<JoinGame>(bytes).is_ok());
+
+        // A stream that never ends, like an open connection, is only read up
+        // to the first trailing byte.
+        let stream = std::io::Read::chain(bytes, std::io::repeat(0));
+        let err = strict.deserialize_from::<_, JoinGame>(stream).unwrap_err();
+        assert_eq!(&crate::Error::TrailingBytes(1), err.inner());
+        assert_eq!(Some(bytes.len()), err.offset());
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct ChatMessage {
+        message: crate::types::bounded_string::BoundedString<4>,
+    }
+
+    #[test]
+    fn strings() {
+        use crate::types::bounded_string::BoundedString;
+
+        // "a\u{e9}\u{1f600}" is 7 bytes of UTF-8 but 4 UTF-16 code units.
+        let value = ChatMessage {
+            message: BoundedString::new("a\u{e9}\u{1f600}").unwrap(),
+        };
+        let bytes: &[u8] = &[0x07, b'a', 0xc3, 0xa9, 0xf0, 0x9f, 0x98, 0x80];
+
+        let deserialized: ChatMessage = crate::de::from_bytes(bytes).unwrap();
+        assert_eq!(value, deserialized);
+
+        let serialized = crate::ser::to_bytes(&value).unwrap();
+        assert_eq!(bytes.to_vec(), serialized.as_slice());
+
+        assert_eq!(
+            Err(crate::Error::LengthLimitExceeded {
+                length: 5,
+                limit: 4
+            }),
+            BoundedString::<4>::new("abcde")
+        );
+        let err = crate::de::from_bytes::<ChatMessage>(&[0x05, b'a', b'b', b'c', b'd', b'e']);
+        assert_eq!(
+            &crate::Error::LengthLimitExceeded {
+                length: 5,
+                limit: 4
+            },
+            err.unwrap_err().inner()
+        );
+        // Too long to fit before any of it is read.
+        let err = crate::de::from_bytes::<ChatMessage>(&[0x0d]).unwrap_err();
+        assert_eq!(
+            &crate::Error::LengthLimitExceeded {
+                length: 13,
+                limit: 12
+            },
+            err.inner()
+        );
+
+        // Human-readable formats read it back as a plain string.
+        let str_deserializer =
+            serde::de::value::StrDeserializer::<serde::de::value::Error>::new("abcd");
+        assert_eq!(
+            BoundedString::<4>::new("abcd").unwrap(),
+            BoundedString::<4>::deserialize(str_deserializer).unwrap()
+        );
+        let str_deserializer =
+            serde::de::value::StrDeserializer::<serde::de::value::Error>::new("abcde");
+        assert!(BoundedString::<4>::deserialize(str_deserializer).is_err());
+
+        let limits = crate::Limits {
+            max_string_utf16_length: Some(3),
+            ..crate::Limits::default()
+        };
+        let options = crate::Options::new().with_limits(limits);
+        let err = options.deserialize::<String>(bytes).unwrap_err();
+        assert_eq!(
+            &crate::Error::LengthLimitExceeded {
+                length: 4,
+                limit: 3
+            },
+            err.inner()
+        );
+        let err = options.deserialize::<String>(&[0x0a; 11]).unwrap_err();
+        assert_eq!(
+            &crate::Error::LengthLimitExceeded {
+                length: 10,
+                limit: 9
+            },
+            err.inner()
+        );
+
+        let invalid = crate::de::from_bytes::<String>(&[0x02, 0xc3, 0x28]);
+        assert_eq!(&crate::Error::InvalidUtf8, invalid.unwrap_err().inner());
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct MyNewtypes {
+        ids: Vec<VarInt>,
+        time: Option<VarLong>,
+        pair: (VarInt, VarLong),
+    }
+
+    #[test]
+    fn varint_newtype() {
+        let value = MyNewtypes {
+            ids: vec![VarInt(1), VarInt(128)],
+            time: Some(VarLong(-1)),
+            pair: (VarInt::from(2), 3.into()),
+        };
+        let bytes: &[u8] = &[
+            0x02, 0x01, 0x80, 0x01, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0x01, 0x02, 0x03,
+        ];
+
+        let deserialized: MyNewtypes = crate::de::from_bytes(bytes).unwrap();
+        assert_eq!(value, deserialized);
+
+        let serialized = crate::ser::to_bytes(&value).unwrap();
+        assert_eq!(bytes.to_vec(), serialized.as_slice());
+
+        let mut id = VarInt(5) + VarInt(3) * VarInt(2);
+        id -= VarInt(1);
+        assert_eq!(VarInt(10), id);
+        assert_eq!(-10, i32::from(-id));
+        assert_eq!("-1", VarLong(-1).to_string());
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct ForgeHandshake {
+        #[serde(with = "crate::types::varu32")]
+        registry_id: u32,
+        entity_ids: Vec<VarU64>,
+        length: VarUsize,
+        payload_length: VarShort,
+    }
+
+    #[test]
+    fn unsigned_varint() {
+        let value = ForgeHandshake {
+            registry_id: u32::MAX,
+            entity_ids: vec![VarU64(u64::MAX), VarU64(0)],
+            length: VarUsize(300),
+            payload_length: VarShort(0x12345),
+        };
+        let bytes: &[u8] = &[
+            0xff, 0xff, 0xff, 0xff, 0x0f, 0x02, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0x01, 0x00, 0xac, 0x02, 0xa3, 0x45, 0x02,
+        ];
+
+        let deserialized: ForgeHandshake = crate::de::from_bytes(bytes).unwrap();
+        assert_eq!(value, deserialized);
+
+        let serialized = crate::ser::to_bytes(&value).unwrap();
+        assert_eq!(bytes.to_vec(), serialized.as_slice());
+
+        let short: VarShort = crate::de::from_bytes(&[0x01, 0x02]).unwrap();
+        assert_eq!(VarShort(0x102), short);
+        assert!(crate::ser::to_bytes(&VarShort(0x80_0000)).is_err());
+
+        let err = crate::de::from_bytes::<VarU32>(&[0xff, 0xff, 0xff, 0xff, 0x1f]).unwrap_err();
+        assert_eq!(&crate::Error::VarIntTooLong, err.inner());
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct RakNetFrame {
+        #[serde(with = "crate::types::le::u24")]
+        sequence: u32,
+        #[serde(with = "crate::types::le::u16")]
+        length: u16,
+        id: i32,
+        #[serde(with = "crate::types::le::f32")]
+        yaw: f32,
+    }
+
+    #[test]
+    fn little_endian() {
+        let value = RakNetFrame {
+            sequence: 0x010203,
+            length: 0x0405,
+            id: 6,
+            yaw: 1.0,
+        };
+        let tests: [(crate::Options, &[u8]); 2] = [
+            (
+                crate::Options::new(),
+                &[0x03, 0x02, 0x01, 0x05, 0x04, 0, 0, 0, 6, 0, 0, 0x80, 0x3f],
+            ),
+            (
+                crate::Options::new().with_little_endian(),
+                &[0x03, 0x02, 0x01, 0x05, 0x04, 6, 0, 0, 0, 0, 0, 0x80, 0x3f],
+            ),
+        ];
+
+        for (options, bytes) in &tests {
+            let deserialized: RakNetFrame = options.deserialize(bytes).unwrap();
+            assert_eq!(value, deserialized);
+
+            let serialized = options.serialize(&value).unwrap();
+            assert_eq!(bytes.to_vec(), serialized.as_slice());
+        }
+
+        let overflow = RakNetFrame {
+            sequence: 0x0100_0000,
+            ..value
+        };
+        assert!(crate::ser::to_bytes(&overflow).is_err());
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct LoginSuccess {
+        uuid: Uuid,
+        #[serde(with = "crate::types::uuid::hyphenated")]
+        legacy_uuid: Uuid,
+        checksum: i128,
+    }
+
+    #[test]
+    fn uuid() {
+        let uuid = Uuid::from_u64_pair(0x069a79f444e94726, 0xa5befca90e38aaf5);
+        let value = LoginSuccess {
+            uuid,
+            legacy_uuid: uuid,
+            checksum: -2,
+        };
+        let mut bytes = vec![
+            0x06, 0x9a, 0x79, 0xf4, 0x44, 0xe9, 0x47, 0x26, 0xa5, 0xbe, 0xfc, 0xa9, 0x0e, 0x38,
+            0xaa, 0xf5, 0x24,
+        ];
+        bytes.extend_from_slice(b"069a79f4-44e9-4726-a5be-fca90e38aaf5");
+        bytes.extend_from_slice(&(-2i128).to_be_bytes());
+
+        let deserialized: LoginSuccess = crate::de::from_bytes(&bytes).unwrap();
+        assert_eq!(value, deserialized);
+
+        let deserialized: LoginSuccess = crate::de::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(value, deserialized);
+
+        let serialized = crate::ser::to_bytes(&value).unwrap();
+        assert_eq!(bytes, serialized);
+
+        assert_eq!(Ok(uuid), "069a79f444e94726a5befca90e38aaf5".parse());
+        assert!("069a79f4-44e9-4726-a5be+fca90e38aaf5"
+            .parse::<Uuid>()
+            .is_err());
+        assert_eq!((0x069a79f444e94726, 0xa5befca90e38aaf5), uuid.as_u64_pair());
+
+        let little = crate::Options::new().with_little_endian();
+        let pair = Uuid::from_u64_pair(1, 2);
+        let serialized = little.serialize(&pair).unwrap();
+        assert_eq!(
+            vec![1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0],
+            serialized
+        );
+        assert_eq!(pair, little.deserialize(&serialized).unwrap());
+
+        let varint = crate::Options::new().with_varint_encoding();
+        let serialized = varint.serialize(&(pair, 1u64)).unwrap();
+        assert_eq!(
+            vec![0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2, 1],
+            serialized
+        );
+        assert_eq!((pair, 1u64), varint.deserialize(&serialized).unwrap());
+    }
+
+    #[test]
+    fn chars() {
+        let utf8 = crate::Options::new();
+        let utf16 = crate::Options::new().with_utf16_chars();
+        let tests: [(char, crate::Options, &[u8]); 5] = [
+            ('A', utf8, &[0x01, b'A']),
+            ('é', utf8, &[0x02, 0xc3, 0xa9]),
+            ('🦀', utf8, &[0x04, 0xf0, 0x9f, 0xa6, 0x80]),
+            ('A', utf16, &[0x00, 0x41]),
+            ('é', utf16, &[0x00, 0xe9]),
+        ];
+
+        for (goal, options, bytes) in &tests {
+            let deserialized: char = options.deserialize(bytes).unwrap();
+            assert_eq!(goal, &deserialized);
+
+            let serialized = options.serialize(goal).unwrap();
+            assert_eq!(bytes.to_vec(), serialized.as_slice());
+        }
+
+        assert!(utf16.serialize(&'🦀').is_err());
+        let err = utf16.deserialize::<char>(&[0xd8, 0x3e]).unwrap_err();
+        assert_eq!(&crate::Error::InvalidChar(0xd83e), err.inner());
+        let err = utf8.deserialize::<char>(&[0x02, b'A', b'B']).unwrap_err();
+        assert_eq!(&crate::Error::InvalidData, err.inner());
+        let err = utf8.deserialize::<char>(&[0x01, 0xff]).unwrap_err();
+        assert_eq!(&crate::Error::InvalidUtf8, err.inner());
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct CustomPayload {
+        #[serde(with = "varint")]
+        id: i32,
+        #[serde(with = "crate::types::byte_array")]
+        signature: Vec<u8>,
+        #[serde(with = "crate::types::remaining_bytes")]
+        data: Vec<u8>,
+    }
+
+    #[test]
+    fn byte_arrays() {
+        let value = CustomPayload {
+            id: 300,
+            signature: vec![0xaa, 0xbb],
+            data: vec![0x01, 0x02, 0x03],
+        };
+        let bytes: &[u8] = &[0xac, 0x02, 0x02, 0xaa, 0xbb, 0x01, 0x02, 0x03];
+
+        let deserialized: CustomPayload = crate::de::from_bytes(bytes).unwrap();
+        assert_eq!(value, deserialized);
+
+        let deserialized: CustomPayload = crate::de::from_reader(bytes).unwrap();
+        assert_eq!(value, deserialized);
+
+        let serialized = crate::ser::to_bytes(&value).unwrap();
+        assert_eq!(bytes.to_vec(), serialized.as_slice());
+
+        let plain = crate::ser::to_bytes(&vec![0xaau8, 0xbb]).unwrap();
+        assert_eq!(&bytes[2..5], plain.as_slice());
+
+        let limits = crate::Limits {
+            max_bytes: Some(7),
+            ..Default::default()
+        };
+        let options = crate::Options::new().with_limits(limits);
+        let err = options.deserialize::<CustomPayload>(bytes).unwrap_err();
+        assert_eq!(&crate::Error::ByteLimitExceeded { limit: 7 }, err.inner());
+        let err = options
+            .deserialize_from::<_, CustomPayload>(bytes)
+            .unwrap_err();
+        assert_eq!(&crate::Error::ByteLimitExceeded { limit: 7 }, err.inner());
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct KeepAlive {
+        id: i64,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Ping;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    enum Packet {
+        #[serde(rename = "15")]
+        KeepAlive(KeepAlive),
+        #[serde(rename = "32")]
+        Ping(Ping),
+        #[serde(rename = "33")]
+        Pong((), VarInt),
+    }
+
+    #[test]
+    fn unit_and_newtype_variant() {
+        let tests: [(Packet, &[u8]); 3] = [
+            (
+                Packet::KeepAlive(KeepAlive { id: 7 }),
+                &[0x0f, 0, 0, 0, 0, 0, 0, 0, 7],
+            ),
+            (Packet::Ping(Ping), &[0x20]),
+            (Packet::Pong((), VarInt(1)), &[0x21, 0x01]),
+        ];
+
+        for (goal, bytes) in &tests {
+            let deserialized: Packet = crate::de::from_bytes(bytes).unwrap();
+            assert_eq!(goal, &deserialized);
+
+            let serialized = crate::ser::to_bytes(goal).unwrap();
+            assert_eq!(bytes.to_vec(), serialized.as_slice());
+        }
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct SpawnEntity {
+        id: VarInt,
+        reserved: crate::types::skip::Bytes<2>,
+        #[serde(skip)]
+        cached: bool,
+        kind: u8,
+        rest: crate::types::skip::Rest,
+    }
+
+    #[test]
+    fn skipping() {
+        let value = SpawnEntity {
+            id: VarInt(1),
+            reserved: crate::types::skip::Bytes,
+            cached: false,
+            kind: 3,
+            rest: crate::types::skip::Rest,
+        };
+        let bytes: &[u8] = &[0x01, 0xaa, 0xbb, 0x03, 0x01, 0x02, 0x03];
+
+        let options = crate::Options::new().reject_trailing_bytes();
+        let deserialized: SpawnEntity = options.deserialize(bytes).unwrap();
+        assert_eq!(value, deserialized);
+
+        let deserialized: SpawnEntity = options.deserialize_from(bytes).unwrap();
+        assert_eq!(value, deserialized);
+
+        let serialized = crate::ser::to_bytes(&value).unwrap();
+        assert_eq!(vec![0x01, 0, 0, 0x03], serialized);
+        let deserialized: SpawnEntity = options.deserialize(&serialized).unwrap();
+        assert_eq!(value, deserialized);
+
+        let err = crate::de::from_bytes::<(u8, serde::de::IgnoredAny)>(bytes).unwrap_err();
+        assert_eq!(&crate::Error::CannotSkip, err.inner());
+    }
+
+    #[test]
+    fn size() {
+        let values = (
+            MySequences {
+                biomes: vec![1, -1],
+                names: vec!["minecraft:overworld".to_string(); 3],
+                data: vec![0xca, 0xfe],
+            },
+            CustomPayload {
+                id: 300,
+                signature: vec![0xaa; 200],
+                data: vec![0x01],
+            },
+            Packet::KeepAlive(KeepAlive { id: 7 }),
+            Some('é'),
+        );
+        let bytes = crate::ser::to_bytes(&values).unwrap();
+        assert_eq!(bytes.len(), crate::serialized_size(&values).unwrap());
+
+        let options = crate::Options::new().with_varint_encoding();
+        let bytes = options.serialize(&values).unwrap();
+        assert_eq!(bytes.len(), options.serialized_size(&values).unwrap());
+    }
+
+    #[test]
+    fn reused_buffer() {
+        let mut buf = vec![0xff];
+        crate::to_bytes_into(&mut buf, &Packet::Ping(Ping)).unwrap();
+        crate::to_bytes_into(&mut buf, &VarLong(-1)).unwrap();
+        let mut expected = vec![0xff, 0x20];
+        expected.extend_from_slice(&varlong::to_bytes(-1));
+        assert_eq!(expected, buf);
+
+        let mut serializer = crate::Serializer::new(vec![]);
+        serializer.write_varint(-1).unwrap();
+        serializer.write_varlong(300).unwrap();
+        assert_eq!(
+            vec![0xff, 0xff, 0xff, 0xff, 0x0f, 0xac, 0x02],
+            serializer.into_inner()
+        );
+
+        let mut stack = [0u8; 5];
+        assert_eq!(&[0x80, 0x01], varint::encode(128, &mut stack));
+    }
+
+    fn decode_all(bytes: &[u8]) {
+        let _ = crate::de::from_bytes::<MyVarint>(bytes);
+        let _ = crate::de::from_bytes::<MyVarlong>(bytes);
+        let _ = crate::de::from_bytes::<MyContainer>(bytes);
+        let _ = crate::de::from_bytes::<ChunkData>(bytes);
+        let _ = crate::de::from_bytes::<MyTaggedEnums>(bytes);
+        let _ = crate::de::from_bytes::<MyOption>(bytes);
+        let _ = crate::de::from_bytes::<MyMap>(bytes);
+        let _ = crate::de::from_bytes::<MySequences>(bytes);
+        let _ = crate::de::from_bytes::<PluginMessage>(bytes);
+        let _ = crate::de::from_bytes::<CustomPayload>(bytes);
+        let _ = crate::de::from_bytes::<JoinGame>(bytes);
+        let _ = crate::de::from_bytes::<std::collections::HashMap<String, Vec<u8>>>(bytes);
+        let _ = crate::de::from_reader::<_, JoinGame>(bytes);
+        let _ = crate::de::from_reader::<_, MyTaggedEnums>(bytes);
+    }
+
+    #[test]
+    fn never_panics() {
+        let valid: [&[u8]; 4] = [
+            &[0x01, 0x4, b'T', b'E', b'S', b'T'],
+            &[0, 0, 0, 1, 0x02, 0x01, b'A', 0x01, 0x01, b'B', 0x00],
+            &[0x02, 0x01, b'A', 0, 0, 0, 1, 0x01, b'B', 0, 0, 1, 0],
+            &[
+                0x03, 0xff, 0xff, 0xff, 0xff, 0x14, b'm', b'i', b'n', b'e', b'c', b'r', b'a', b'f',
+                b't', b':', b't', b'h', b'e', b'_', b'n', b'e', b't', b'h', b'e', b'r', 0x01, 0x00,
+            ],
+        ];
+
+        // Every truncation and single byte corruption of known good input.
+        for bytes in &valid {
+            for len in 0..=bytes.len() {
+                decode_all(&bytes[..len]);
+            }
+            for i in 0..bytes.len() {
+                for v in &[0x00, 0x01, 0x02, 0x7f, 0x80, 0xff] {
+                    let mut corrupted = bytes.to_vec();
+                    corrupted[i] = *v;
+                    decode_all(&corrupted);
+                }
+            }
+        }
+
+        // Pseudo-random input from a fixed xorshift seed.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..4096 {
+            let len = (next() % 48) as usize;
+            let bytes: Vec<u8> = (0..len).map(|_| next() as u8).collect();
+            decode_all(&bytes);
+        }
+
+        // Nesting deep enough to overflow the stack without a depth limit.
+        let deep = vec![1u8; 2_000_000];
+        let limit = crate::Error::DepthLimitExceeded {
+            limit: crate::DEFAULT_MAX_DEPTH,
+        };
+        let err = crate::de::from_bytes::<Node>(&deep).unwrap_err();
+        assert_eq!(&limit, err.inner());
+        let err = crate::de::from_reader::<_, Node>(deep.as_slice()).unwrap_err();
+        assert_eq!(&limit, err.inner());
+        let err = crate::Options::new()
+            .deserialize::<Node>(&deep)
+            .unwrap_err();
+        assert_eq!(&limit, err.inner());
+        let err = crate::de::from_bytes::<NewtypeNode>(&deep).unwrap_err();
+        assert_eq!(&limit, err.inner());
+        let shallow: NewtypeNode = crate::de::from_bytes(&[1, 0]).unwrap();
+        assert_eq!(NewtypeNode(Some(Box::new(NewtypeNode(None)))), shallow);
+
+        // Deserializers other than ours go through the byte sequence fallback.
+        let seq =
+            serde::de::value::SeqDeserializer::<_, crate::Error>::new(vec![0xffu8; 6].into_iter());
+        assert!(crate::types::varint::deserialize(seq).is_err());
+        let seq = serde::de::value::SeqDeserializer::<_, crate::Error>::new(
+            vec![0xff, 0xff, 0xff, 0xff, 0x7f].into_iter(),
+        );
+        let err = crate::types::varint::deserialize(seq).unwrap_err();
+        assert_eq!(crate::Error::VarIntTooLong.to_string(), err.to_string());
+        let mut overflow = vec![0xffu8; 9];
+        overflow.push(0x7f);
+        let seq = serde::de::value::SeqDeserializer::<_, crate::Error>::new(overflow.into_iter());
+        let err = crate::types::varlong::deserialize(seq).unwrap_err();
+        assert_eq!(crate::Error::VarIntTooLong.to_string(), err.to_string());
+        let seq =
+            serde::de::value::SeqDeserializer::<_, crate::Error>::new(vec![0x80u8].into_iter());
+        assert!(crate::types::varlong::deserialize(seq).is_err());
+    }
 }
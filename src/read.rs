@@ -0,0 +1,191 @@
+use std::io;
+
+use crate::error::{Error, Result};
+
+/// Bytes handed out by a [`Read`] source, either borrowed from the input for
+/// `'de` or copied into a scratch buffer that only lives until the next read.
+pub enum Reference<'b, 'c> {
+    Borrowed(&'b [u8]),
+    Copied(&'c [u8]),
+}
+
+pub trait Read<'de> {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()>;
+
+    fn read_bytes<'s>(&'s mut self, len: usize) -> Result<Reference<'de, 's>>;
+
+    /// Number of bytes consumed so far.
+    fn offset(&self) -> usize;
+
+    /// Whether the input has been read to the end. Readers may have to wait
+    /// for one more byte to tell.
+    fn is_at_end(&mut self) -> Result<bool>;
+
+    /// Reads the rest of the input, but no more than `max` bytes.
+    fn read_to_end<'s>(&'s mut self, max: usize) -> Result<Reference<'de, 's>>;
+
+    /// Consumes the rest of the input, returning how many bytes were left.
+    fn skip_to_end(&mut self) -> Result<usize>;
+
+    /// Number of bytes left in the input, without consuming them. Readers
+    /// that can't tell without waiting for the end of a stream stop counting
+    /// at the first byte.
+    fn trailing_bytes(&mut self) -> Result<usize>;
+}
+
+pub struct SliceRead<'de> {
+    slice: &'de [u8],
+    index: usize,
+}
+
+impl<'de> SliceRead<'de> {
+    pub fn new(slice: &'de [u8]) -> Self {
+        SliceRead { slice, index: 0 }
+    }
+
+    /// The part of the input that has not been read yet.
+    pub fn remaining(&self) -> &'de [u8] {
+        &self.slice[self.index..]
+    }
+}
+
+impl<'de> Read<'de> for SliceRead<'de> {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        match self.read_bytes(buf.len())? {
+            Reference::Borrowed(bytes) | Reference::Copied(bytes) => buf.copy_from_slice(bytes),
+        }
+        Ok(())
+    }
+
+    fn read_bytes<'s>(&'s mut self, len: usize) -> Result<Reference<'de, 's>> {
+        if self.slice.len() - self.index < len {
+            self.index = self.slice.len();
+            return Err(Error::Eof);
+        }
+        let bytes = &self.slice[self.index..self.index + len];
+        self.index += len;
+        Ok(Reference::Borrowed(bytes))
+    }
+
+    fn offset(&self) -> usize {
+        self.index
+    }
+
+    fn is_at_end(&mut self) -> Result<bool> {
+        Ok(self.index == self.slice.len())
+    }
+
+    fn read_to_end<'s>(&'s mut self, max: usize) -> Result<Reference<'de, 's>> {
+        let len = max.min(self.slice.len() - self.index);
+        self.read_bytes(len)
+    }
+
+    fn skip_to_end(&mut self) -> Result<usize> {
+        let len = self.slice.len() - self.index;
+        self.index = self.slice.len();
+        Ok(len)
+    }
+
+    fn trailing_bytes(&mut self) -> Result<usize> {
+        Ok(self.slice.len() - self.index)
+    }
+}
+
+pub struct IoRead<R> {
+    reader: R,
+    scratch: Vec<u8>,
+    offset: usize,
+    // A byte read ahead by `is_at_end`, not consumed yet.
+    peeked: Option<u8>,
+}
+
+impl<R: io::Read> IoRead<R> {
+    pub fn new(reader: R) -> Self {
+        IoRead {
+            reader,
+            scratch: vec![],
+            offset: 0,
+            peeked: None,
+        }
+    }
+}
+
+impl<R: io::Read> IoRead<R> {
+    // Reads up to `max` bytes into the scratch buffer, starting with the
+    // peeked byte. Reading through `take` grows the buffer with the data that
+    // actually arrives instead of trusting `max` up front.
+    fn read_into_scratch(&mut self, max: usize) -> Result<usize> {
+        self.scratch.clear();
+        if max > 0 {
+            self.scratch.extend(self.peeked.take());
+        }
+        let rest = (max - self.scratch.len()) as u64;
+        io::Read::read_to_end(
+            &mut io::Read::take(&mut self.reader, rest),
+            &mut self.scratch,
+        )?;
+        self.offset += self.scratch.len();
+        Ok(self.scratch.len())
+    }
+}
+
+impl<'de, R: io::Read> Read<'de> for IoRead<R> {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        let start = match (self.peeked, buf.first_mut()) {
+            (Some(byte), Some(first)) => {
+                *first = byte;
+                self.peeked = None;
+                1
+            }
+            _ => 0,
+        };
+        self.reader.read_exact(&mut buf[start..])?;
+        self.offset += buf.len();
+        Ok(())
+    }
+
+    fn read_bytes<'s>(&'s mut self, len: usize) -> Result<Reference<'de, 's>> {
+        if self.read_into_scratch(len)? < len {
+            return Err(Error::Eof);
+        }
+        Ok(Reference::Copied(&self.scratch))
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn is_at_end(&mut self) -> Result<bool> {
+        if self.peeked.is_some() {
+            return Ok(false);
+        }
+        let mut buf = [0u8; 1];
+        loop {
+            match self.reader.read(&mut buf) {
+                Ok(0) => return Ok(true),
+                Ok(_) => {
+                    self.peeked = Some(buf[0]);
+                    return Ok(false);
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    fn read_to_end<'s>(&'s mut self, max: usize) -> Result<Reference<'de, 's>> {
+        self.read_into_scratch(max)?;
+        Ok(Reference::Copied(&self.scratch))
+    }
+
+    fn skip_to_end(&mut self) -> Result<usize> {
+        let peeked = self.peeked.take().is_some() as usize;
+        let len = peeked + io::copy(&mut self.reader, &mut io::sink())? as usize;
+        self.offset += len;
+        Ok(len)
+    }
+
+    fn trailing_bytes(&mut self) -> Result<usize> {
+        Ok(!self.is_at_end()? as usize)
+    }
+}
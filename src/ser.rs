@@ -1,22 +1,174 @@
-//use crate::VarInt;
+use std::io::Write;
+
 use serde::{ser, Serialize};
 
+use crate::config::{CharEncoding, Endian, IntEncoding, Options};
 use crate::error::{Error, Result};
+use crate::types::enum_tag::EnumTag;
+use crate::types::zigzag_encode;
+
+macro_rules! serialize_fixed {
+    ($self:ident, $v:expr) => {
+        match $self.options.endian {
+            Endian::Big => $self.write_all(&$v.to_be_bytes()),
+            Endian::Little => $self.write_all(&$v.to_le_bytes()),
+        }
+    };
+}
+
+pub struct Serializer<W> {
+    output: W,
+    // Set by an `enum_tag` wrapper for the enum it wraps directly, cleared by
+    // anything else that could contain an enum.
+    tag: Option<EnumTag>,
+    raw: bool,
+    options: Options,
+}
+
+impl<W: Write> Serializer<W> {
+    pub fn new(output: W) -> Self {
+        Serializer {
+            output,
+            tag: None,
+            raw: false,
+            options: Options::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.output
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        self.output.write_all(buf).map_err(Error::from)
+    }
+
+    fn write_leb128(&mut self, value: u64) -> Result<()> {
+        self.write_all(crate::types::leb128(value, &mut [0u8; 10]))
+    }
+
+    /// Writes `value` as a VarInt straight to the output.
+    pub fn write_varint(&mut self, value: i32) -> Result<()> {
+        self.write_leb128(value as u32 as u64)
+    }
+
+    /// Writes `value` as a VarLong straight to the output.
+    pub fn write_varlong(&mut self, value: i64) -> Result<()> {
+        self.write_leb128(value as u64)
+    }
+
+    // Variants renamed to an integer, e.g. `#[serde(rename = "1")]`, are written
+    // with that discriminant instead of their position in the enum. A rename
+    // must not reuse the position of a variant that isn't renamed, decoding
+    // rejects such IDs as ambiguous.
+    fn write_variant(&mut self, variant_index: u32, variant: &str) -> Result<()> {
+        let id = variant.parse::<i32>().unwrap_or(variant_index as i32);
+        match self.tag.take().unwrap_or(EnumTag::VarInt) {
+            EnumTag::VarInt => self.write_varint(id),
+            EnumTag::U8 => {
+                if !(0..=u8::MAX as i32).contains(&id) {
+                    return Err(Error::InvalidData);
+                }
+                self.write_all(&[id as u8])
+            }
+            EnumTag::I32 => serialize_fixed!(self, id),
+            EnumTag::String => ser::Serializer::serialize_str(self, variant),
+        }
+    }
 
-pub struct Serializer {
-    output: Vec<u8>,
+    fn write_length(&mut self, len: usize) -> Result<()> {
+        if len > i32::MAX as usize {
+            return Err(Error::LengthLimitExceeded {
+                length: len,
+                limit: i32::MAX as usize,
+            });
+        }
+        self.write_varint(len as i32)
+    }
 }
 
 pub fn to_bytes<T>(value: &T) -> Result<Vec<u8>>
 where
     T: Serialize,
 {
-    let mut serializer = Serializer { output: vec![] };
+    let mut serializer = Serializer::new(vec![]);
     value.serialize(&mut serializer)?;
-    Ok(serializer.output)
+    Ok(serializer.into_inner())
+}
+
+/// Appends `value` to `output`, so one buffer can be reused across packets.
+pub fn to_bytes_into<T>(output: &mut Vec<u8>, value: &T) -> Result<()>
+where
+    T: ?Sized + Serialize,
+{
+    value.serialize(&mut Serializer::new(output))
+}
+
+pub fn to_writer<W, T>(writer: &mut W, value: &T) -> Result<()>
+where
+    W: Write,
+    T: ?Sized + Serialize,
+{
+    let mut serializer = Serializer::new(writer);
+    value.serialize(&mut serializer)
+}
+
+/// Number of bytes `value` takes when serialized, computed without writing
+/// them anywhere.
+pub fn serialized_size<T>(value: &T) -> Result<usize>
+where
+    T: ?Sized + Serialize,
+{
+    let mut serializer = Serializer::new(SizeCounter(0));
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_inner().0)
+}
+
+// A writer that only counts what goes through it.
+pub(crate) struct SizeCounter(pub(crate) usize);
+
+impl Write for SizeCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
-impl<'a> ser::Serializer for &'a mut Serializer {
+macro_rules! serialize_int {
+    ($name:ident, $ty:ty, $unsigned:ty) => {
+        fn $name(self, v: $ty) -> Result<()> {
+            match self.options.int_encoding {
+                IntEncoding::Fixed => serialize_fixed!(self, v),
+                // Unsigned integers have nothing to ZigZag.
+                IntEncoding::ZigZag if <$ty>::MIN != 0 => {
+                    self.write_leb128(zigzag_encode(v as i64))
+                }
+                IntEncoding::VarInt | IntEncoding::ZigZag => {
+                    self.write_leb128(v as $unsigned as u64)
+                }
+            }
+        }
+    };
+}
+
+macro_rules! serialize_float {
+    ($name:ident, $ty:ty) => {
+        fn $name(self, v: $ty) -> Result<()> {
+            serialize_fixed!(self, v)
+        }
+    };
+}
+
+impl<W: Write> ser::Serializer for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -29,149 +181,151 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     type SerializeStructVariant = Self;
 
     fn serialize_bool(self, v: bool) -> Result<()> {
-        self.output.push(v as u8);
-        Ok(())
+        self.write_all(&[v as u8])
     }
 
     fn serialize_i8(self, v: i8) -> Result<()> {
-        self.output.push(unsafe { std::mem::transmute(v) });
-        Ok(())
+        self.write_all(&[v as u8])
     }
 
-    fn serialize_i16(self, v: i16) -> Result<()> {
-        self.output.extend(&v.to_be_bytes());
-        Ok(())
-    }
+    serialize_int!(serialize_i16, i16, u16);
+    serialize_int!(serialize_i32, i32, u32);
+    serialize_int!(serialize_i64, i64, u64);
 
-    fn serialize_i32(self, v: i32) -> Result<()> {
-        self.output.extend(&v.to_be_bytes());
-        Ok(())
-    }
-
-    fn serialize_i64(self, v: i64) -> Result<()> {
-        self.output.extend(&v.to_be_bytes());
-        Ok(())
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        serialize_fixed!(self, v)
     }
 
     fn serialize_u8(self, v: u8) -> Result<()> {
-        self.output.extend(&v.to_be_bytes());
-        Ok(())
-    }
-
-    fn serialize_u16(self, v: u16) -> Result<()> {
-        self.output.extend(&v.to_be_bytes());
-        Ok(())
-    }
-
-    fn serialize_u32(self, v: u32) -> Result<()> {
-        self.output.extend(&v.to_be_bytes());
-        Ok(())
-    }
-
-    fn serialize_u64(self, v: u64) -> Result<()> {
-        self.output.extend(&v.to_be_bytes());
-        Ok(())
+        self.write_all(&v.to_be_bytes())
     }
 
-    fn serialize_f32(self, v: f32) -> Result<()> {
-        self.output.extend(&v.to_be_bytes());
-        Ok(())
-    }
+    serialize_int!(serialize_u16, u16, u16);
+    serialize_int!(serialize_u32, u32, u32);
+    serialize_int!(serialize_u64, u64, u64);
 
-    fn serialize_f64(self, v: f64) -> Result<()> {
-        self.output.extend(&v.to_be_bytes());
-        Ok(())
+    fn serialize_u128(self, v: u128) -> Result<()> {
+        serialize_fixed!(self, v)
     }
+    serialize_float!(serialize_f32, f32);
+    serialize_float!(serialize_f64, f64);
 
     fn serialize_char(self, v: char) -> Result<()> {
-        self.output.push(v as u8);
-        Ok(())
+        match self.options.char_encoding {
+            CharEncoding::Utf8 => self.serialize_str(v.encode_utf8(&mut [0u8; 4])),
+            CharEncoding::Utf16 => {
+                let mut buf = [0u16; 2];
+                match v.encode_utf16(&mut buf) {
+                    [unit] => serialize_fixed!(self, *unit),
+                    _ => Err(Error::InvalidChar(v as u32)),
+                }
+            }
+        }
     }
 
     fn serialize_str(self, v: &str) -> Result<()> {
-        let mut value = v.len();
-        let mut out = vec![];
-        while {
-            let mut temp = (value & 0x7F) as u8;
-            value >>= 7;
-            if value != 0 {
-                temp |= 0x80;
-            }
-            out.push(temp);
-            value != 0
-        } {}
-
-        self.output.extend(&out);
-        self.output.extend(v.as_bytes());
-        Ok(())
+        self.write_length(v.len())?;
+        self.write_all(v.as_bytes())
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<()> {
-        self.output.extend(v);
-        Ok(())
+        if !std::mem::take(&mut self.raw) {
+            self.write_length(v.len())?;
+        }
+        self.write_all(v)
     }
 
     fn serialize_none(self) -> Result<()> {
-        unimplemented!()
+        self.serialize_bool(false)
     }
 
     fn serialize_some<T>(self, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        unimplemented!()
+        self.tag = None;
+        self.serialize_bool(true)?;
+        value.serialize(self)
     }
 
     fn serialize_unit(self) -> Result<()> {
-        todo!()
+        Ok(())
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
-        todo!()
+        Ok(())
     }
 
     fn serialize_unit_variant(
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<()> {
-        self.serialize_bytes(&crate::types::varint::to_bytes(variant_index as i32))
+        self.write_variant(variant_index, variant)
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
+        if let Some(tag) = EnumTag::from_name(name) {
+            self.tag = Some(tag);
+            let result = value.serialize(&mut *self);
+            self.tag = None;
+            return result;
+        }
+        if name == crate::types::RAW {
+            self.raw = true;
+            let result = value.serialize(&mut *self);
+            self.raw = false;
+            return result;
+        }
+        if name == crate::types::uuid::NAME {
+            let options = self.options;
+            self.options.int_encoding = IntEncoding::Fixed;
+            let result = value.serialize(&mut *self);
+            self.options = options;
+            return result;
+        }
+        self.tag = None;
         value.serialize(self)
     }
 
     fn serialize_newtype_variant<T>(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         variant: &'static str,
         value: &T,
     ) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        todo!()
+        self.write_variant(variant_index, variant)?;
+        value.serialize(self)
     }
 
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        self.tag = None;
+        let len = len.ok_or_else(|| {
+            Error::Message("sequences must have a known length to be serialized".to_string())
+        })?;
+        self.write_length(len)?;
         Ok(self)
     }
 
-    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        self.tag = None;
         Ok(self)
     }
 
     fn serialize_tuple_struct(
         self,
         _name: &'static str,
-        len: usize,
+        _len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
+        self.tag = None;
         Ok(self)
     }
 
@@ -179,18 +333,24 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        self.serialize_bytes(&crate::types::varint::to_bytes(variant_index as i32))?;
+        self.write_variant(variant_index, variant)?;
         Ok(self)
     }
 
-    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        todo!()
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
+        self.tag = None;
+        let len = len.ok_or_else(|| {
+            Error::Message("maps must have a known length to be serialized".to_string())
+        })?;
+        self.write_length(len)?;
+        Ok(self)
     }
 
-    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        self.tag = None;
         Ok(self)
     }
 
@@ -198,10 +358,10 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        self.serialize_bytes(&crate::types::varint::to_bytes(variant_index as i32))?;
+        self.write_variant(variant_index, variant)?;
         Ok(self)
     }
 
@@ -210,7 +370,7 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     }
 }
 
-impl<'a> ser::SerializeSeq for &'a mut Serializer {
+impl<W: Write> ser::SerializeSeq for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -226,7 +386,7 @@ impl<'a> ser::SerializeSeq for &'a mut Serializer {
     }
 }
 
-impl<'a> ser::SerializeTuple for &'a mut Serializer {
+impl<W: Write> ser::SerializeTuple for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -242,7 +402,7 @@ impl<'a> ser::SerializeTuple for &'a mut Serializer {
     }
 }
 
-impl<'a> ser::SerializeTupleStruct for &'a mut Serializer {
+impl<W: Write> ser::SerializeTupleStruct for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -258,7 +418,7 @@ impl<'a> ser::SerializeTupleStruct for &'a mut Serializer {
     }
 }
 
-impl<'a> ser::SerializeTupleVariant for &'a mut Serializer {
+impl<W: Write> ser::SerializeTupleVariant for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -274,7 +434,7 @@ impl<'a> ser::SerializeTupleVariant for &'a mut Serializer {
     }
 }
 
-impl<'a> ser::SerializeMap for &'a mut Serializer {
+impl<W: Write> ser::SerializeMap for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -297,11 +457,11 @@ impl<'a> ser::SerializeMap for &'a mut Serializer {
     }
 }
 
-impl<'a> ser::SerializeStruct for &'a mut Serializer {
+impl<W: Write> ser::SerializeStruct for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
@@ -313,11 +473,11 @@ impl<'a> ser::SerializeStruct for &'a mut Serializer {
     }
 }
 
-impl<'a> ser::SerializeStructVariant for &'a mut Serializer {
+impl<W: Write> ser::SerializeStructVariant for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
@@ -0,0 +1,111 @@
+use std::fmt;
+use std::ops::Deref;
+
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::{Error, Result};
+
+/// A string of at most `N` UTF-16 code units, the unit the vanilla server
+/// uses for its string limits. The limit is checked on both encode and decode.
+///
+/// Human-readable formats such as JSON read it back as a plain string. Other
+/// binary formats can write it but not read it back, as they see a tuple
+/// struct where they wrote a string.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BoundedString<const N: usize>(String);
+
+pub(crate) fn utf16_length(value: &str) -> usize {
+    value.chars().map(char::len_utf16).sum()
+}
+
+impl<const N: usize> BoundedString<N> {
+    pub fn new(value: impl Into<String>) -> Result<Self> {
+        let value = value.into();
+        match utf16_length(&value) {
+            length if length > N => Err(Error::LengthLimitExceeded { length, limit: N }),
+            _ => Ok(BoundedString(value)),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl<const N: usize> Deref for BoundedString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<const N: usize> fmt::Display for BoundedString<N> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.0)
+    }
+}
+
+impl<const N: usize> From<BoundedString<N>> for String {
+    fn from(value: BoundedString<N>) -> Self {
+        value.0
+    }
+}
+
+impl<const N: usize> Serialize for BoundedString<N> {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match utf16_length(&self.0) {
+            length if length > N => Err(ser::Error::custom(Error::LengthLimitExceeded {
+                length,
+                limit: N,
+            })),
+            _ => serializer.serialize_str(&self.0),
+        }
+    }
+}
+
+pub(crate) const NAME: &str = "$basalt_serde::bounded_string";
+
+impl<'de, const N: usize> Deserialize<'de> for BoundedString<N> {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_string(BoundedStringVisitor)
+        } else {
+            deserializer.deserialize_tuple_struct(NAME, N, BoundedStringVisitor)
+        }
+    }
+}
+
+struct BoundedStringVisitor<const N: usize>;
+
+impl<'de, const N: usize> de::Visitor<'de> for BoundedStringVisitor<N> {
+    type Value = BoundedString<N>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a string of at most {} UTF-16 code units", N)
+    }
+
+    fn visit_str<E>(self, v: &str) -> ::std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_string(v.to_string())
+    }
+
+    fn visit_string<E>(self, v: String) -> ::std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        BoundedString::new(v).map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{
+    de::{SeqAccess, Visitor},
+    Serializer,
+};
+
+/// Bytes prefixed with their length as a VarInt, the protocol's byte array.
+/// Plain `Vec<u8>` fields are encoded the same way, one element at a time.
+pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<Vec<u8>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    d.deserialize_byte_buf(ByteArrayVisitor)
+}
+
+pub(crate) struct ByteArrayVisitor;
+
+impl<'de> Visitor<'de> for ByteArrayVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a byte array")
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> ::std::result::Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> ::std::result::Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> ::std::result::Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut bytes = vec![];
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+pub fn serialize<T, S>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ?Sized + AsRef<[u8]>,
+    S: Serializer,
+{
+    serializer.serialize_bytes(bytes.as_ref())
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{Deserialize, Deserializer, Visitor};
+
+// The wrapper modules tell the serializer which tag encoding to use for the
+// enum they wrap by passing it through a newtype with one of these names.
+const VARINT: &str = "$basalt_serde::enum_tag::varint";
+const U8: &str = "$basalt_serde::enum_tag::u8";
+const I32: &str = "$basalt_serde::enum_tag::i32";
+const STRING: &str = "$basalt_serde::enum_tag::string";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum EnumTag {
+    VarInt,
+    U8,
+    I32,
+    String,
+}
+
+impl EnumTag {
+    pub(crate) fn from_name(name: &str) -> Option<EnumTag> {
+        match name {
+            VARINT => Some(EnumTag::VarInt),
+            U8 => Some(EnumTag::U8),
+            I32 => Some(EnumTag::I32),
+            STRING => Some(EnumTag::String),
+            _ => None,
+        }
+    }
+}
+
+struct TaggedVisitor<T> {
+    phantom: PhantomData<T>,
+}
+
+impl<'de, T> Visitor<'de> for TaggedVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an enum with a custom tag encoding")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+    }
+}
+
+macro_rules! enum_tag {
+    ($(#[$attr:meta])* $module:ident, $name:ident) => {
+        $(#[$attr])*
+        pub mod $module {
+            use serde::{Deserialize, Serialize, Serializer};
+
+            pub fn deserialize<'de, D, T>(d: D) -> ::std::result::Result<T, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+                T: Deserialize<'de>,
+            {
+                d.deserialize_newtype_struct(
+                    super::$name,
+                    super::TaggedVisitor {
+                        phantom: std::marker::PhantomData,
+                    },
+                )
+            }
+
+            pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+                T: Serialize,
+            {
+                serializer.serialize_newtype_struct(super::$name, value)
+            }
+        }
+    };
+}
+
+enum_tag!(
+    /// Tags the enum with a VarInt, the default for enums without a wrapper.
+    varint,
+    VARINT
+);
+enum_tag!(
+    /// Tags the enum with a single unsigned byte.
+    u8,
+    U8
+);
+enum_tag!(
+    /// Tags the enum with a fixed-width 32-bit integer.
+    i32,
+    I32
+);
+enum_tag!(
+    /// Tags the enum with its variant name as a length-prefixed string.
+    string,
+    STRING
+);
@@ -0,0 +1,120 @@
+// Per-field little-endian integers and floats, for formats that mix byte
+// orders, e.g. `#[serde(with = "basalt_serde::types::le::u24")]` for RakNet
+// sequence numbers. They ignore the `Endian` option.
+
+macro_rules! le {
+    ($name:ident, $ty:ty, $len:expr) => {
+        pub mod $name {
+            use serde::{
+                de::{SeqAccess, Visitor},
+                Serializer,
+            };
+
+            pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<$ty, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                d.deserialize_tuple($len, LeVisitor)
+            }
+
+            struct LeVisitor;
+
+            impl<'de> Visitor<'de> for LeVisitor {
+                type Value = $ty;
+
+                fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    write!(formatter, "a little-endian {}", stringify!($name))
+                }
+
+                fn visit_seq<A>(self, seq: A) -> ::std::result::Result<Self::Value, A::Error>
+                where
+                    A: SeqAccess<'de>,
+                {
+                    Ok(<$ty>::from_le_bytes(super::visit_bytes(seq, &self)?))
+                }
+            }
+
+            pub fn serialize<S>(value: &$ty, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                crate::types::serialize_raw(&value.to_le_bytes(), serializer)
+            }
+        }
+    };
+}
+
+le!(i16, i16, 2);
+le!(u16, u16, 2);
+le!(i32, i32, 4);
+le!(u32, u32, 4);
+le!(i64, i64, 8);
+le!(u64, u64, 8);
+le!(f32, f32, 4);
+le!(f64, f64, 8);
+
+/// Largest value a `u24` can hold.
+pub const U24_MAX: u32 = 0xFF_FFFF;
+
+/// A 24-bit unsigned integer stored in a `u32`, as used by RakNet.
+pub mod u24 {
+    use serde::{
+        de::{SeqAccess, Visitor},
+        ser, Serializer,
+    };
+
+    pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<u32, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        d.deserialize_tuple(3, U24Visitor)
+    }
+
+    struct U24Visitor;
+
+    impl<'de> Visitor<'de> for U24Visitor {
+        type Value = u32;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a little-endian u24")
+        }
+
+        fn visit_seq<A>(self, seq: A) -> ::std::result::Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let [a, b, c] = super::visit_bytes(seq, &self)?;
+            Ok(u32::from_le_bytes([a, b, c, 0]))
+        }
+    }
+
+    pub fn serialize<S>(value: &u32, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if *value > super::U24_MAX {
+            return Err(ser::Error::custom(format_args!(
+                "{} does not fit in a u24",
+                value
+            )));
+        }
+        crate::types::serialize_raw(&value.to_le_bytes()[..3], serializer)
+    }
+}
+
+fn visit_bytes<'de, A, const N: usize>(
+    mut seq: A,
+    expected: &dyn serde::de::Expected,
+) -> Result<[u8; N], A::Error>
+where
+    A: serde::de::SeqAccess<'de>,
+{
+    let mut bytes = [0u8; N];
+    for (count, byte) in bytes.iter_mut().enumerate() {
+        *byte = match seq.next_element::<u8>()? {
+            Some(read) => read,
+            None => return Err(serde::de::Error::invalid_length(count, expected)),
+        };
+    }
+    Ok(bytes)
+}
@@ -1,3 +1,151 @@
+// Conversions, formatting and arithmetic shared by the integer newtypes.
+macro_rules! newtype_ops {
+    ($name:ident, $ty:ty) => {
+        impl From<$ty> for $name {
+            fn from(v: $ty) -> Self {
+                $name(v)
+            }
+        }
+
+        impl From<$name> for $ty {
+            fn from(v: $name) -> Self {
+                v.0
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                std::fmt::Display::fmt(&self.0, formatter)
+            }
+        }
+
+        newtype_ops!(@binary $name, Add, add, AddAssign, add_assign);
+        newtype_ops!(@binary $name, Sub, sub, SubAssign, sub_assign);
+        newtype_ops!(@binary $name, Mul, mul, MulAssign, mul_assign);
+        newtype_ops!(@binary $name, Div, div, DivAssign, div_assign);
+        newtype_ops!(@binary $name, Rem, rem, RemAssign, rem_assign);
+    };
+    (@neg $name:ident) => {
+        impl std::ops::Neg for $name {
+            type Output = $name;
+
+            fn neg(self) -> $name {
+                $name(-self.0)
+            }
+        }
+    };
+    (@binary $name:ident, $op:ident, $method:ident, $assign:ident, $assign_method:ident) => {
+        impl std::ops::$op for $name {
+            type Output = $name;
+
+            fn $method(self, rhs: $name) -> $name {
+                $name(std::ops::$op::$method(self.0, rhs.0))
+            }
+        }
+
+        impl std::ops::$assign for $name {
+            fn $assign_method(&mut self, rhs: $name) {
+                std::ops::$assign::$assign_method(&mut self.0, rhs.0)
+            }
+        }
+    };
+}
+
+pub mod bounded_string;
+pub mod byte_array;
+pub mod enum_tag;
+pub mod le;
+pub mod remaining_bytes;
+pub mod skip;
+pub mod uuid;
 pub mod varint;
 pub mod varlong;
+pub mod varshort;
+pub mod varu32;
+pub mod varu64;
+pub mod varusize;
 pub mod vec_sized;
+pub mod vec_unsized;
+pub mod zigzag_varint;
+pub mod zigzag_varlong;
+
+use serde::{
+    de::{self, Expected, SeqAccess},
+    Serialize, Serializer,
+};
+
+use crate::error::Error;
+
+// Lets the wrapper modules write encoded bytes as they are, `Serializer` would
+// otherwise length-prefix them like any other byte array.
+pub(crate) const RAW: &str = "$basalt_serde::raw";
+
+pub(crate) fn serialize_raw<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_newtype_struct(RAW, &RawBytes(bytes))
+}
+
+struct RawBytes<'a>(&'a [u8]);
+
+impl Serialize for RawBytes<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+// Encodes `value` into the front of `buf`, which has to be large enough for
+// it: 5 bytes for 32-bit values and 10 for 64-bit ones.
+pub(crate) fn leb128(mut value: u64, buf: &mut [u8]) -> &[u8] {
+    let mut len = 0;
+    loop {
+        buf[len] = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            return &buf[..=len];
+        }
+        buf[len] |= 0x80;
+        len += 1;
+    }
+}
+
+pub(crate) fn zigzag_encode(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+pub(crate) fn zigzag_decode(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+// Decodes an unsigned LEB128 value of at most `bits` bits for deserializers
+// that visit the encoded bytes as a tuple.
+pub(crate) fn visit_leb128<'de, A>(
+    mut seq: A,
+    bits: u32,
+    expected: &dyn Expected,
+) -> Result<u64, A::Error>
+where
+    A: SeqAccess<'de>,
+{
+    let mut result = 0u64;
+    let mut shift = 0;
+    loop {
+        let read = match seq.next_element::<u8>()? {
+            Some(read) => read,
+            None => return Err(de::Error::invalid_length((shift / 7) as usize, expected)),
+        };
+        let value = (read & 0x7F) as u64;
+        if shift >= bits || (bits - shift < 7 && value >> (bits - shift) != 0) {
+            return Err(de::Error::custom(Error::VarIntTooLong));
+        }
+        result |= value << shift;
+        if read & 0x80 == 0 {
+            return Ok(result);
+        }
+        shift += 7;
+    }
+}
@@ -0,0 +1,22 @@
+use serde::Serializer;
+
+use super::byte_array::ByteArrayVisitor;
+
+pub(crate) const NAME: &str = "$basalt_serde::remaining_bytes";
+
+/// Everything up to the end of the input, without a length prefix, e.g. the
+/// data of a plugin message. Only valid as the last field of a packet.
+pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<Vec<u8>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    d.deserialize_tuple_struct(NAME, usize::MAX, ByteArrayVisitor)
+}
+
+pub fn serialize<T, S>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ?Sized + AsRef<[u8]>,
+    S: Serializer,
+{
+    super::serialize_raw(bytes.as_ref(), serializer)
+}
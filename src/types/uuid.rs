@@ -0,0 +1,142 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::{Error, Result};
+
+/// A UUID, written as its most significant long followed by its least
+/// significant one. The longs are always 8 bytes wide whatever the
+/// `IntEncoding`, but follow the `Endian` option: big-endian by default, as in
+/// Java Edition, or little-endian for Bedrock.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Uuid(pub u128);
+
+impl Uuid {
+    pub fn from_u64_pair(most: u64, least: u64) -> Self {
+        Uuid((most as u128) << 64 | least as u128)
+    }
+
+    pub fn as_u64_pair(&self) -> (u64, u64) {
+        ((self.0 >> 64) as u64, self.0 as u64)
+    }
+
+    pub fn from_bytes(bytes: [u8; 16]) -> Self {
+        Uuid(u128::from_be_bytes(bytes))
+    }
+
+    pub fn to_bytes(&self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+}
+
+impl From<u128> for Uuid {
+    fn from(v: u128) -> Self {
+        Uuid(v)
+    }
+}
+
+impl From<Uuid> for u128 {
+    fn from(v: Uuid) -> Self {
+        v.0
+    }
+}
+
+/// The hyphenated form, e.g. `069a79f4-44e9-4726-a5be-fca90e38aaf5`.
+impl fmt::Display for Uuid {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let hex = format!("{:032x}", self.0);
+        write!(
+            formatter,
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        )
+    }
+}
+
+/// Parses the hyphenated form, or the 32 digit form Mojang's web API uses.
+impl FromStr for Uuid {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::Message(format!("invalid UUID {:?}", s));
+        let hyphenated = s.len() == 36 && [8, 13, 18, 23].iter().all(|&i| s.as_bytes()[i] == b'-');
+        let hex = if hyphenated {
+            s.replace('-', "")
+        } else {
+            s.to_string()
+        };
+        if hex.len() != 32 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        u128::from_str_radix(&hex, 16)
+            .map(Uuid)
+            .map_err(|_| invalid())
+    }
+}
+
+pub(crate) const NAME: &str = "$basalt_serde::uuid";
+
+impl Serialize for Uuid {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(NAME, &self.as_u64_pair())
+    }
+}
+
+impl<'de> Deserialize<'de> for Uuid {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(NAME, UuidVisitor)
+    }
+}
+
+struct UuidVisitor;
+
+impl<'de> de::Visitor<'de> for UuidVisitor {
+    type Value = Uuid;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a UUID as two longs")
+    }
+
+    fn visit_newtype_struct<D>(
+        self,
+        deserializer: D,
+    ) -> ::std::result::Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (most, least) = <(u64, u64)>::deserialize(deserializer)?;
+        Ok(Uuid::from_u64_pair(most, least))
+    }
+}
+
+/// A `Uuid` written as its hyphenated string, e.g. the Login Success UUID
+/// before 1.16.
+pub mod hyphenated {
+    use super::*;
+
+    pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<Uuid, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(d)?;
+        value.parse().map_err(de::Error::custom)
+    }
+
+    pub fn serialize<S>(uuid: &Uuid, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(uuid)
+    }
+}
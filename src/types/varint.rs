@@ -1,66 +1,76 @@
 use serde::{
     de::{SeqAccess, Visitor},
-    Serializer,
+    Deserialize, Deserializer, Serialize, Serializer,
 };
 
-pub type VarInt = i32;
+/// An `i32` that is always encoded as a VarInt, usable anywhere a type is
+/// expected, e.g. `Vec<VarInt>` or `Option<VarInt>`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarInt(pub i32);
 
-pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<VarInt, D::Error>
+newtype_ops!(VarInt, i32);
+newtype_ops!(@neg VarInt);
+
+impl Serialize for VarInt {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for VarInt {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize(d).map(VarInt)
+    }
+}
+
+pub(crate) const NAME: &str = "$basalt_serde::varint";
+
+pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<i32, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    d.deserialize_tuple(5, VarIntVisitor)
+    d.deserialize_tuple_struct(NAME, 5, VarIntVisitor)
 }
 
 pub struct VarIntVisitor;
 
 impl<'de> Visitor<'de> for VarIntVisitor {
-    type Value = VarInt;
+    type Value = i32;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
         formatter.write_str("a VarInt encoded as bytes")
     }
 
-    fn visit_seq<A>(self, mut seq: A) -> ::std::result::Result<Self::Value, A::Error>
+    fn visit_i32<E>(self, v: i32) -> ::std::result::Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> ::std::result::Result<Self::Value, A::Error>
     where
         A: SeqAccess<'de>,
     {
-        let mut count = 0;
-        let mut result = 0u32;
-        let mut read;
-        while {
-            read = seq.next_element::<u8>()?.unwrap();
-            let value = read & 0x7F;
-            result |= (value as u32) << (7 * count);
-            count += 1;
-            if count > 5 {
-                panic!("Error not implemented");
-            }
-            (read & 0x80) != 0
-        } {}
-        Ok(unsafe { std::mem::transmute(result) })
+        Ok(super::visit_leb128(seq, 32, &self)? as u32 as i32)
     }
 }
 
-pub fn to_bytes(varint: i32) -> Vec<u8> {
-    let mut value = unsafe { std::mem::transmute::<i32, u32>(varint) };
-    let mut out = vec![];
-    while {
-        let mut temp = (value & 0x7F) as u8;
-        value >>= 7;
-        if value != 0 {
-            temp |= 0x80;
-        }
-        out.push(temp);
-        value != 0
-    } {}
+/// Encodes `varint` into `buf` without allocating, returning the bytes used.
+pub fn encode(varint: i32, buf: &mut [u8; 5]) -> &[u8] {
+    super::leb128(varint as u32 as u64, buf)
+}
 
-    out
+pub fn to_bytes(varint: i32) -> Vec<u8> {
+    encode(varint, &mut [0u8; 5]).to_vec()
 }
 
-pub fn serialize<S>(varint: &VarInt, serializer: S) -> Result<S::Ok, S::Error>
+pub fn serialize<S>(varint: &i32, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    serializer.serialize_bytes(&to_bytes(*varint))
+    super::serialize_raw(encode(*varint, &mut [0u8; 5]), serializer)
 }
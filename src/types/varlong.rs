@@ -1,59 +1,76 @@
 use serde::{
     de::{SeqAccess, Visitor},
-    Serializer,
+    Deserialize, Deserializer, Serialize, Serializer,
 };
 
-pub type VarLong = i64;
+/// An `i64` that is always encoded as a VarLong, usable anywhere a type is
+/// expected, e.g. `Vec<VarLong>` or `Option<VarLong>`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarLong(pub i64);
 
-pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<VarLong, D::Error>
+newtype_ops!(VarLong, i64);
+newtype_ops!(@neg VarLong);
+
+impl Serialize for VarLong {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for VarLong {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize(d).map(VarLong)
+    }
+}
+
+pub(crate) const NAME: &str = "$basalt_serde::varlong";
+
+pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<i64, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    d.deserialize_tuple(10, VarLongVisitor)
+    d.deserialize_tuple_struct(NAME, 10, VarLongVisitor)
 }
 
 struct VarLongVisitor;
 
 impl<'de> Visitor<'de> for VarLongVisitor {
-    type Value = VarLong;
+    type Value = i64;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
         formatter.write_str("a VarLong encoded as bytes")
     }
 
-    fn visit_seq<A>(self, mut seq: A) -> ::std::result::Result<Self::Value, A::Error>
+    fn visit_i64<E>(self, v: i64) -> ::std::result::Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> ::std::result::Result<Self::Value, A::Error>
     where
         A: SeqAccess<'de>,
     {
-        let mut count = 0;
-        let mut result = 0u64;
-        let mut read;
-        while {
-            read = seq.next_element::<u8>()?.unwrap();
-            let value = read & 0x7F;
-            result |= (value as u64) << (7 * count);
-            count += 1;
-            (read & 0x80) != 0
-        } {}
-        Ok(unsafe { std::mem::transmute(result) })
+        Ok(super::visit_leb128(seq, 64, &self)? as i64)
     }
 }
 
-pub fn serialize<S>(varlong: &VarLong, serializer: S) -> Result<S::Ok, S::Error>
+/// Encodes `varlong` into `buf` without allocating, returning the bytes used.
+pub fn encode(varlong: i64, buf: &mut [u8; 10]) -> &[u8] {
+    super::leb128(varlong as u64, buf)
+}
+
+pub fn to_bytes(varlong: i64) -> Vec<u8> {
+    encode(varlong, &mut [0u8; 10]).to_vec()
+}
+
+pub fn serialize<S>(varlong: &i64, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    let mut value = unsafe { std::mem::transmute::<i64, u64>(*varlong) };
-    let mut out = vec![];
-    while {
-        let mut temp = (value & 0x7F) as u8;
-        value >>= 7;
-        if value != 0 {
-            temp |= 0x80;
-        }
-        out.push(temp);
-        value != 0
-    } {}
-
-    serializer.serialize_bytes(&out)
+    super::serialize_raw(encode(*varlong, &mut [0u8; 10]), serializer)
 }
@@ -0,0 +1,96 @@
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    ser, Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// Largest value a VarShort can hold, 23 bits.
+pub const MAX: u32 = 0x7F_FFFF;
+
+/// Forge's VarShort: the low 15 bits as a big-endian `u16`, with the top bit
+/// set when another byte carrying bits 15 to 22 follows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarShort(pub u32);
+
+newtype_ops!(VarShort, u32);
+
+impl Serialize for VarShort {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for VarShort {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize(d).map(VarShort)
+    }
+}
+
+pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<u32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    d.deserialize_tuple(3, VarShortVisitor)
+}
+
+struct VarShortVisitor;
+
+impl<'de> Visitor<'de> for VarShortVisitor {
+    type Value = u32;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a VarShort encoded as bytes")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> ::std::result::Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut bytes = [0u8; 3];
+        for count in 0..3 {
+            if count == 2 && bytes[0] & 0x80 == 0 {
+                break;
+            }
+            bytes[count] = match seq.next_element::<u8>()? {
+                Some(read) => read,
+                None => return Err(de::Error::invalid_length(count, &self)),
+            };
+        }
+        let low = u16::from_be_bytes([bytes[0], bytes[1]]) as u32 & 0x7FFF;
+        Ok((bytes[2] as u32) << 15 | low)
+    }
+}
+
+pub fn to_bytes(value: u32) -> Option<Vec<u8>> {
+    if value > MAX {
+        return None;
+    }
+    let high = (value >> 15) as u8;
+    let mut low = (value & 0x7FFF) as u16;
+    if high != 0 {
+        low |= 0x8000;
+    }
+    let mut out = low.to_be_bytes().to_vec();
+    if high != 0 {
+        out.push(high);
+    }
+    Some(out)
+}
+
+pub fn serialize<S>(value: &u32, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match to_bytes(*value) {
+        Some(bytes) => super::serialize_raw(&bytes, serializer),
+        None => Err(ser::Error::custom(format_args!(
+            "{} does not fit in a VarShort",
+            value
+        ))),
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{
+    de::{SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// A `u32` that is always encoded as an unsigned VarInt, e.g. a length or a
+/// registry ID. Values that do not fit in 32 bits are rejected on decode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarU32(pub u32);
+
+newtype_ops!(VarU32, u32);
+
+impl Serialize for VarU32 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for VarU32 {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize(d).map(VarU32)
+    }
+}
+
+pub(crate) const NAME: &str = "$basalt_serde::varu32";
+
+pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<u32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    d.deserialize_tuple_struct(NAME, 5, VarU32Visitor)
+}
+
+struct VarU32Visitor;
+
+impl<'de> Visitor<'de> for VarU32Visitor {
+    type Value = u32;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an unsigned VarInt encoded as bytes")
+    }
+
+    fn visit_u32<E>(self, v: u32) -> ::std::result::Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> ::std::result::Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        Ok(super::visit_leb128(seq, 32, &self)? as u32)
+    }
+}
+
+pub fn serialize<S>(value: &u32, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    super::serialize_raw(super::leb128(*value as u64, &mut [0u8; 5]), serializer)
+}
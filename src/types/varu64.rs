@@ -0,0 +1,66 @@
+use serde::{
+    de::{SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// A `u64` that is always encoded as an unsigned VarLong, e.g. a length or a
+/// registry ID. Values that do not fit in 64 bits are rejected on decode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarU64(pub u64);
+
+newtype_ops!(VarU64, u64);
+
+impl Serialize for VarU64 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for VarU64 {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize(d).map(VarU64)
+    }
+}
+
+pub(crate) const NAME: &str = "$basalt_serde::varu64";
+
+pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    d.deserialize_tuple_struct(NAME, 10, VarU64Visitor)
+}
+
+struct VarU64Visitor;
+
+impl<'de> Visitor<'de> for VarU64Visitor {
+    type Value = u64;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an unsigned VarLong encoded as bytes")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> ::std::result::Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> ::std::result::Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        super::visit_leb128(seq, 64, &self)
+    }
+}
+
+pub fn serialize<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    super::serialize_raw(super::leb128(*value, &mut [0u8; 10]), serializer)
+}
@@ -0,0 +1,71 @@
+use std::convert::TryFrom;
+
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// A `usize` that is always encoded as an unsigned VarLong, e.g. a length or a
+/// registry ID. Values that do not fit in a `usize` are rejected on decode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarUsize(pub usize);
+
+newtype_ops!(VarUsize, usize);
+
+impl Serialize for VarUsize {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for VarUsize {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize(d).map(VarUsize)
+    }
+}
+
+pub(crate) const NAME: &str = "$basalt_serde::varusize";
+
+pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<usize, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    d.deserialize_tuple_struct(NAME, 10, VarUsizeVisitor)
+}
+
+struct VarUsizeVisitor;
+
+impl<'de> Visitor<'de> for VarUsizeVisitor {
+    type Value = usize;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an unsigned VarLong encoded as bytes")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> ::std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        usize::try_from(v).map_err(|_| de::Error::custom(crate::error::Error::VarIntTooLong))
+    }
+
+    fn visit_seq<A>(self, seq: A) -> ::std::result::Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        Ok(super::visit_leb128(seq, usize::BITS, &self)? as usize)
+    }
+}
+
+pub fn serialize<S>(value: &usize, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    super::serialize_raw(super::leb128(*value as u64, &mut [0u8; 10]), serializer)
+}
@@ -1,21 +1,22 @@
-use std::marker::PhantomData;
 use serde::{
     de::{SeqAccess, Visitor},
-    ser::SerializeSeq,
-    Serializer,
-    Serialize,
-    Deserialize
+    ser::SerializeTuple,
+    Deserialize, Serialize, Serializer,
 };
+use std::marker::PhantomData;
+
+pub(crate) const NAME: &str = "$basalt_serde::vec_unsized";
 
 pub fn deserialize<'de, D, T>(d: D) -> ::std::result::Result<Vec<T>, D::Error>
 where
     D: serde::Deserializer<'de>,
     T: Sized + Deserialize<'de>,
 {
-    d.deserialize_tuple(
+    d.deserialize_tuple_struct(
+        NAME,
         usize::MAX,
         VecVisitor {
-            phantom: PhantomData::default(),
+            phantom: PhantomData,
         },
     )
 }
@@ -31,7 +32,7 @@ where
     type Value = Vec<T>;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-        formatter.write_str("a VarInt followed by data")
+        formatter.write_str("data until the end of the input")
     }
 
     fn visit_seq<A>(self, mut seq: A) -> ::std::result::Result<Self::Value, A::Error>
@@ -40,11 +41,8 @@ where
         T: Deserialize<'de>,
     {
         let mut data = vec![];
-        loop {
-            match seq.next_element::<T>() {
-                Ok(v) => data.push(v.unwrap()),
-                Err(_) => break,
-            }
+        while let Some(v) = seq.next_element::<T>()? {
+            data.push(v);
         }
         Ok(data)
     }
@@ -53,11 +51,11 @@ where
 pub fn serialize<T, S>(vec: &Vec<T>, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
-    T: Serialize
+    T: Serialize,
 {
-    let mut sequence = serializer.serialize_seq(Some(vec.len()))?;
+    let mut sequence = serializer.serialize_tuple(vec.len())?;
     for v in vec {
-	sequence.serialize_element(v)?;
+        sequence.serialize_element(v)?;
     }
     sequence.end()
 }
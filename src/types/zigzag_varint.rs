@@ -0,0 +1,46 @@
+use serde::{
+    de::{SeqAccess, Visitor},
+    Serializer,
+};
+
+use super::{zigzag_decode, zigzag_encode};
+
+pub(crate) const NAME: &str = "$basalt_serde::zigzag_varint";
+
+pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<i32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    d.deserialize_tuple_struct(NAME, 5, ZigZagVarIntVisitor)
+}
+
+struct ZigZagVarIntVisitor;
+
+impl<'de> Visitor<'de> for ZigZagVarIntVisitor {
+    type Value = i32;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a ZigZag VarInt encoded as bytes")
+    }
+
+    fn visit_i32<E>(self, v: i32) -> ::std::result::Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> ::std::result::Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        Ok(zigzag_decode(super::visit_leb128(seq, 32, &self)?) as i32)
+    }
+}
+
+pub fn serialize<S>(value: &i32, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    super::serialize_raw(
+        super::leb128(zigzag_encode(*value as i64), &mut [0u8; 5]),
+        serializer,
+    )
+}
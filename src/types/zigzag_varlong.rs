@@ -0,0 +1,46 @@
+use serde::{
+    de::{SeqAccess, Visitor},
+    Serializer,
+};
+
+use super::{zigzag_decode, zigzag_encode};
+
+pub(crate) const NAME: &str = "$basalt_serde::zigzag_varlong";
+
+pub fn deserialize<'de, D>(d: D) -> ::std::result::Result<i64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    d.deserialize_tuple_struct(NAME, 10, ZigZagVarLongVisitor)
+}
+
+struct ZigZagVarLongVisitor;
+
+impl<'de> Visitor<'de> for ZigZagVarLongVisitor {
+    type Value = i64;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a ZigZag VarLong encoded as bytes")
+    }
+
+    fn visit_i64<E>(self, v: i64) -> ::std::result::Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> ::std::result::Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        Ok(zigzag_decode(super::visit_leb128(seq, 64, &self)?))
+    }
+}
+
+pub fn serialize<S>(value: &i64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    super::serialize_raw(
+        super::leb128(zigzag_encode(*value), &mut [0u8; 10]),
+        serializer,
+    )
+}